@@ -0,0 +1,53 @@
+use std::time::{Duration, Instant};
+
+use chess::core::chess::{board::Board, position::Position};
+
+// The start position and Kiwipete, with the depth each is counted to.
+const POSITIONS: [(&str, usize); 2] = [
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 5),
+    ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 4),
+];
+
+/// `perft` with the moves of the mailbox generator, to compare the bitboards against.
+fn perft_mailbox(board: &mut Board, depth: usize) -> usize {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = board.legal_moves_mailbox();
+    if depth == 1 {
+        return moves.len();
+    }
+    let mut nodes = 0;
+    for mv in moves {
+        let undo = board.make_move(mv);
+        nodes += perft_mailbox(board, depth - 1);
+        board.unmake_move(mv, undo);
+    }
+    nodes
+}
+
+fn report(name: &str, nodes: usize, elapsed: Duration) {
+    let nodes_per_second = nodes as f64 / elapsed.as_secs_f64();
+    println!("  {:<10} {:>12} nodes in {:>8.3} s, {:>12.0} nodes/s", name, nodes, elapsed.as_secs_f64(), nodes_per_second);
+}
+
+fn main() {
+    for (fen, depth) in POSITIONS {
+        let board = Position::from_fen(fen).unwrap().board;
+        println!("perft({}) of {}", depth, fen);
+
+        let start = Instant::now();
+        let bitboard_nodes = board.perft(depth);
+        let bitboard_time = start.elapsed();
+        report("bitboards", bitboard_nodes, bitboard_time);
+
+        let mut mailbox_board = board;
+        let start = Instant::now();
+        let mailbox_nodes = perft_mailbox(&mut mailbox_board, depth);
+        let mailbox_time = start.elapsed();
+        report("mailbox", mailbox_nodes, mailbox_time);
+
+        assert_eq!(bitboard_nodes, mailbox_nodes, "the generators count different positions");
+        println!("  {:.1}x faster with bitboards", mailbox_time.as_secs_f64() / bitboard_time.as_secs_f64());
+    }
+}
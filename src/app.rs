@@ -0,0 +1,356 @@
+use std::{sync::{Arc, RwLock}, time::Duration};
+
+#[cfg(target_os = "macos")]
+use mac_notification_sys::{Notification, Sound, send_notification};
+use ratatui::{Frame, crossterm::event::{Event, KeyCode, poll, read}, layout::{Alignment, Constraint, Direction, Layout, Margin, Rect}, widgets::{Block, Borders, Paragraph, Wrap}};
+use thousands::Separable;
+use tui_input::{Input, backend::crossterm::EventHandler};
+
+use chess::{core::{chess::{board::Board, board_state::Evaluation, chess_move::Move, pgn::{PgnGame, game_result}, position::Position, square::square_name}, engine::handle::{Engine, ThreadStat}, search::SearchLimits}, log};
+
+pub const DEFAULT_MOVE_TIME: Duration = Duration::from_secs(5);
+// The most moves of the expected line shown, which is as many as two lines of the panel hold.
+const PRINCIPAL_VARIATION_LENGTH: usize = 16;
+
+#[derive(Clone)]
+pub struct App {
+    pub engine: Engine,
+    pub frame_count: usize,
+    pub input: Arc<RwLock<Input>>,
+    pub editing: Arc<RwLock<bool>>,
+    pub prompt: Arc<RwLock<String>>,
+    pub start_time: std::time::Instant,
+    /// How long the engine thinks about each of its moves.
+    pub search_limits: SearchLimits,
+    // The evaluation of the search's last completed depth, kept for the move it ends up playing.
+    last_evaluation: Arc<RwLock<Option<Evaluation>>>,
+    // The hanging pieces of the position last drawn, worked out again only once the position changes.
+    hanging_pieces: Option<(Board, String)>,
+}
+
+impl App {
+
+    pub fn new(engine: Engine, search_limits: SearchLimits) -> App {
+        App {
+            engine: engine,
+            frame_count: 0,
+            input: Arc::new(RwLock::new(Input::new(String::from("")))),
+            editing: Arc::new(RwLock::new(true)),
+            prompt: Arc::new(RwLock::new(String::from("Enter move:"))),
+            start_time: std::time::Instant::now(),
+            search_limits: search_limits,
+            last_evaluation: Arc::new(RwLock::new(None)),
+            hanging_pieces: None,
+        }
+    }
+
+    pub fn run(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.engine.start_threads();
+        {
+            let last_evaluation = self.last_evaluation.clone();
+            self.engine.on_progress(move |_, info| *last_evaluation.write().unwrap() = Some(info.evaluation));
+        }
+        {
+            let app = self.clone();
+            self.engine.on_best_move(move |_, best_move| app.play_engine_move(best_move));
+        }
+        {
+            let mut t = self.clone();
+            let _used = std::thread::Builder::new().name(String::from("Drawer")).spawn(move || {
+                let mut terminal = ratatui::init();
+                loop {
+                    terminal.draw(|frame| t.draw(frame)).unwrap();
+                    std::thread::sleep(Duration::from_millis(100));
+                }
+            }).unwrap();
+        }
+        log!("Starting loop");
+        loop {
+            
+            if poll(Duration::from_millis(100))? {
+                log!("Got event");
+                let event = read().unwrap();
+                let mut editing = self.editing.write().unwrap();
+                if *editing {
+                    log!("Editing");
+                    match event {
+                        Event::Key(key_event) => {
+                            log!("Got key event");
+                            if key_event.code == KeyCode::Esc {
+                                log!("Got Esc key event");
+                                *editing = false;
+                            } else if key_event.code == KeyCode::Enter {
+                                log!("Got Enter key event");
+                                *editing = false;
+                                log!("Processing prompt");
+                                drop(editing);
+                                self.process_prompt();
+                                let mut editing = self.editing.write().unwrap();
+                                *editing = true;
+                            } else {
+                                log!("Forward to input");
+                                self.input.write().unwrap().handle_event(&event);
+                            }
+                        },
+                        _ => {},
+                    };
+                } else {
+                    log!("Not editing");
+                    match event {
+                        Event::Key(key_event) => {
+                            log!("Got key event");
+                            if key_event.code == KeyCode::Enter {
+                                log!("Got Enter key event. Entering edit mode.");
+                                *editing = true;
+                            } else if key_event.code == KeyCode::Esc {
+                                log!("Got Esc key event. Exiting application.");
+                                break Ok(());
+                            }
+                        },
+                        _ => {},
+                    };
+                }
+            } else {
+                // log!("Timeout expired. No event.");
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        // let _unused = self.engine.run_lock.write().unwrap();
+        let thread_count = self.engine.thread_stats().len();
+        let [left_pane, right_pane] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![
+                Constraint::Percentage(50),
+                Constraint::Percentage(50),
+            ])
+            .split(frame.area()).as_ref().try_into().unwrap();
+        let [global_status_pane, principal_variation_pane, thread_status_pane] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(5), Constraint::Length(4), Constraint::Fill(1)])
+            .split(right_pane.inner(Margin::new(1, 1))).as_ref().try_into().unwrap();
+
+        let [board_pane, prompt_pane] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(3),
+            ])
+            .split(left_pane).as_ref().try_into().unwrap();
+
+        let status_pane = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(4); thread_count])
+            .split(thread_status_pane.inner(Margin::new(1, 1)));
+
+        frame.render_widget(Block::default().borders(Borders::ALL), board_pane);
+        frame.render_widget(Block::default().borders(Borders::ALL), prompt_pane);
+        frame.render_widget(Block::default().borders(Borders::ALL), right_pane);
+        
+        for (i, thread_stat) in self.engine.thread_stats().iter().enumerate() {
+            App::draw_stat(frame, i, thread_stat, status_pane[i]);
+        }
+
+        let position = self.engine.position();
+        let principal_variation = position.line_to_numbered_san(&self.engine.principal_variation(PRINCIPAL_VARIATION_LENGTH));
+        frame.render_widget(
+            Paragraph::new(principal_variation)
+                .wrap(Wrap { trim: true })
+                .block(Block::default().borders(Borders::ALL).title("Principal variation")),
+            principal_variation_pane,
+        );
+
+        if self.hanging_pieces.as_ref().is_none_or(|(board, _)| *board != position.board) {
+            let hanging_pieces: Vec<String> = position.hanging_pieces().into_iter().map(square_name).collect();
+            let hanging_pieces = if hanging_pieces.is_empty() { String::from("none") } else { hanging_pieces.join(" ") };
+            self.hanging_pieces = Some((position.board, hanging_pieces));
+        }
+        let hanging_pieces = &self.hanging_pieces.as_ref().unwrap().1;
+        frame.render_widget(Paragraph::new(format!("{}\nHanging pieces: {}", position, hanging_pieces)), board_pane.inner(Margin::new(1, 1)));
+        frame.render_widget(Block::default().borders(Borders::ALL), prompt_pane);
+        frame.render_widget(Paragraph::new(self.prompt.read().unwrap().clone()), prompt_pane.inner(Margin::new(1, 0)));
+        frame.render_widget(Paragraph::new(format!("{}", self.input.read().unwrap().value())), prompt_pane.inner(Margin::new(1, 1)));
+
+        // frame.render_widget(Block::default().borders(Borders::ALL), vertical_panes[1]);
+        // frame.render_widget(Block::default().borders(Borders::ALL), vertical_panes[0]);
+
+        let [eval_queue_stat_pane, reval_queue_stat_pane, board_pieces_pane, positions_evaluated_pane, positions_evaluated_pseudo_pane] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(1), Constraint::Length(1), Constraint::Length(1), Constraint::Length(1), Constraint::Length(1)])
+            .split(global_status_pane).as_ref().try_into().unwrap();
+
+        let [eval_queue_stat_name_pane, eval_queue_stat_value_pane] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Fill(1), Constraint::Fill(1)])
+            .split(eval_queue_stat_pane).as_ref().try_into().unwrap();
+
+        let [reval_queue_stat_name_pane, reval_queue_stat_value_pane] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Fill(1), Constraint::Fill(1)])
+            .split(reval_queue_stat_pane).as_ref().try_into().unwrap();
+        let [board_pieces_name_pane, board_pieces_value_pane] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Percentage(50); 2])
+            .split(board_pieces_pane).as_ref().try_into().unwrap();
+        let [positions_evaluated_name_pane, positions_evaluated_value_pane] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Length(21), Constraint::Fill(1)])
+            .split(positions_evaluated_pane).as_ref().try_into().unwrap();
+        let [positions_evaluated_pseudo_name_pane, positions_evaluated_pseudo_value_pane] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Percentage(50); 2])
+            .split(positions_evaluated_pseudo_pane).as_ref().try_into().unwrap();
+
+        frame.render_widget(Paragraph::new("Reval Queue:"), reval_queue_stat_name_pane);
+        let length = self.engine.reevaluation_queue_length();
+        frame.render_widget(Paragraph::new(format!("{}", length.separate_with_commas())).alignment(Alignment::Right), reval_queue_stat_value_pane);
+
+        frame.render_widget(Paragraph::new("Eval Queue:"), eval_queue_stat_name_pane);
+        let lengths = self.engine.evaluation_queue_lengths();
+        let mut lengths_string = String::from("{");
+        for length in lengths.iter() {
+            lengths_string += &format!("{}: {}, ", length.0, length.1.separate_with_commas());
+        }
+        lengths_string += "}";
+        frame.render_widget(Paragraph::new(lengths_string).alignment(Alignment::Right), eval_queue_stat_value_pane);
+        frame.render_widget(Paragraph::new("Positions evaluated:"), positions_evaluated_name_pane);
+        frame.render_widget(Paragraph::new(format!("{}", self.engine.position_counts().separate_with_commas())).alignment(Alignment::Right), positions_evaluated_value_pane);
+        frame.render_widget(Paragraph::new("Positions evaluated pseudo:"), positions_evaluated_pseudo_name_pane);
+        frame.render_widget(Paragraph::new(format!("{}", self.engine.positions_evaluated().separate_with_commas())).alignment(Alignment::Right), positions_evaluated_pseudo_value_pane);
+        frame.render_widget(Paragraph::new(format!("Time: {:?} Engine status: {}", self.start_time.elapsed().as_secs(), self.engine.status())), right_pane);
+        self.frame_count = self.frame_count + 1;
+    }
+
+    fn draw_stat(frame: &mut Frame, index: usize,thread_stat: &ThreadStat, rect: Rect) {
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Percentage(50); 2])
+            .split(rect.inner(Margin::new(1, 1)));
+        let left_bars = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(1); 2])
+            .split(panes[0]);
+        let right_bars = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(1); 2])
+            .split(panes[1]);
+        frame.render_widget(Block::default().borders(Borders::ALL), rect);
+        frame.render_widget(Paragraph::new(format!("Thread #{}", index)), rect.inner(Margin::new(1, 0)));
+        frame.render_widget(Paragraph::new(format!("{}", thread_stat.running_status.read().unwrap())).alignment(Alignment::Right), right_bars[0]);
+        frame.render_widget(Paragraph::new("Positions evaluated"), left_bars[1]);
+        frame.render_widget(Paragraph::new(format!("{}", thread_stat.positions_evaluated_length.read().unwrap().separate_with_commas())).alignment(Alignment::Right), right_bars[1]);
+    }
+
+    fn process_prompt(&mut self) {
+            let mut current_position = self.engine.position();
+            let current_board = current_position.board;
+            let mut input = self.input.write().unwrap();
+            if let Some(fen) = input.value().strip_prefix("fen ") {
+                match Position::from_fen(fen) {
+                    Ok(position) => {
+                        input.reset();
+                        drop(input);
+                        self.engine.set_position(position);
+                        *self.prompt.write().unwrap() = String::from("Position set. Enter move:");
+                    },
+                    Err(error) => {
+                        *self.prompt.write().unwrap() = format!("Invalid FEN: {}. Enter move:", error);
+                        input.reset();
+                    },
+                }
+                return;
+            }
+            if let Some(path) = input.value().strip_prefix("pgn save ") {
+                let path = path.trim().to_string();
+                input.reset();
+                drop(input);
+                *self.prompt.write().unwrap() = match std::fs::write(&path, self.engine.to_pgn(&current_position).to_string()) {
+                    Ok(()) => format!("Game saved to {}. Enter move:", path),
+                    Err(error) => format!("Could not save {}: {}. Enter move:", path, error),
+                };
+                return;
+            }
+            if let Some(path) = input.value().strip_prefix("pgn load ") {
+                let path = path.trim().to_string();
+                input.reset();
+                drop(input);
+                let position = std::fs::read_to_string(&path)
+                    .map_err(|error| error.to_string())
+                    .and_then(|text| PgnGame::parse(&text).and_then(|game| game.replay()).map_err(|error| error.to_string()));
+                match position {
+                    Ok(position) => {
+                        self.engine.set_position(position);
+                        *self.prompt.write().unwrap() = format!("Game loaded from {}. Enter move:", path);
+                    },
+                    Err(error) => {
+                        *self.prompt.write().unwrap() = format!("Could not load {}: {}. Enter move:", path, error);
+                    },
+                }
+                return;
+            }
+            let mv = match current_position.parse_move(input.value()) {
+                Ok(mv) => mv,
+                Err(error) => {
+                    log!("Processing prompt: {}", error);
+                    *self.prompt.write().unwrap() = format!("{}. Enter move:", error);
+                    input.reset();
+                    return;
+                }
+            };
+            input.reset();
+            drop(input);
+            if self.engine.is_searching() {
+                *self.prompt.write().unwrap() = String::from("The engine is still thinking. Enter move:");
+                return;
+            }
+
+            log!("Processing prompt: current_board: {:?} \n{}", current_board.pieces(), current_board);
+            // What the engine thought of the move when it looked at it, from the player's side.
+            let move_evaluation = self.engine.move_evaluation(&current_board, mv);
+            log!("Player played move: {}", current_position.to_san(mv));
+            current_position.play(mv);
+            log!("Position: {}", serde_json::to_string(&current_position.board).unwrap());
+            self.engine.continue_to(current_position.clone());
+            self.engine.set_last_move_evaluation(move_evaluation);
+            if current_position.legal_moves().is_empty() || current_position.draw_reason().is_some() {
+                *self.prompt.write().unwrap() = format!("Game over: {}. Enter move:", game_result(&current_position));
+                return;
+            }
+            *self.prompt.write().unwrap() = String::from("Engine is thinking...");
+            self.engine.start(self.search_limits);
+    }
+
+    /// Plays the move the engine found in reply to the player's.
+    fn play_engine_move(&self, best_move: Option<Move>) {
+        let mut position = self.engine.position();
+        let evaluation = self.last_evaluation.write().unwrap().take();
+        let best_move = match best_move {
+            Some(best_move) => best_move,
+            None => {
+                *self.prompt.write().unwrap() = format!("Game over: {}. Enter move:", game_result(&position));
+                return;
+            }
+        };
+        let san = position.to_san(best_move);
+        log!("Processing prompt: Playing engine move {}", san);
+        position.play(best_move);
+        self.engine.continue_to(position.clone());
+        self.engine.set_last_move_evaluation(evaluation);
+        // The graph search keeps looking ahead while the player thinks, so that the next search starts from there.
+        self.engine.think_ahead();
+        *self.prompt.write().unwrap() = match (position.draw_reason(), game_result(&position)) {
+            (Some(draw_reason), _) => format!("Engine played {}. {}. Enter move:", san, draw_reason),
+            (None, "*") => format!("Engine played {}. Enter move:", san),
+            (None, result) => format!("Engine played {}. Game over: {}. Enter move:", san, result),
+        };
+        #[cfg(target_os = "macos")]
+        send_notification(
+            "NOW",
+            None,
+            "Without subtitle",
+            Some(Notification::new().sound(Sound::Default)),
+        )
+        .unwrap();
+    }
+}
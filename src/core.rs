@@ -6,4 +6,10 @@ pub mod log;
 
 pub mod engine;
 
-pub mod app;
\ No newline at end of file
+pub mod search;
+
+pub mod time_manager;
+
+pub mod uci;
+
+pub mod cecp;
\ No newline at end of file
@@ -0,0 +1,294 @@
+use std::{sync::{Arc, atomic::{AtomicBool, Ordering}}, time::Duration};
+
+use crate::{core::{chess::{board_state::{Evaluation, PositionResult}, chess_move::Move, pgn::game_result, position::{Color, Position}}, engine::handle::Engine, search::{SearchBackend, SearchInfo, SearchLimits, pause_search}}, log};
+
+// XBoard reads a score above this as a mate, with the number of moves to it added on.
+const MATE_SCORE: i64 = 100000;
+
+/// The time control set by `level`, `st` and `sd`, along with the clocks last sent by `time` and `otim`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct TimeControl {
+    pub moves_per_session: usize,
+    pub base: Duration,
+    pub increment: Duration,
+    pub time_per_move: Option<Duration>,
+    pub depth: Option<usize>,
+    pub engine_clock: Option<Duration>,
+    pub opponent_clock: Option<Duration>,
+}
+
+impl TimeControl {
+    /// The limits for a search by the engine playing `side` in `position`.
+    pub fn search_limits(&self, position: &Position, side: Color) -> SearchLimits {
+        let mut limits = SearchLimits { depth: self.depth, movetime: self.time_per_move, ..SearchLimits::default() };
+        if self.time_per_move.is_some() {
+            return limits;
+        }
+        let engine_clock = self.engine_clock.unwrap_or(self.base);
+        let opponent_clock = self.opponent_clock.unwrap_or(self.base);
+        if engine_clock.is_zero() {
+            return limits;
+        }
+        let (engine_time, engine_increment, opponent_time, opponent_increment) = match side {
+            Color::White => (&mut limits.wtime, &mut limits.winc, &mut limits.btime, &mut limits.binc),
+            Color::Black => (&mut limits.btime, &mut limits.binc, &mut limits.wtime, &mut limits.winc),
+        };
+        (*engine_time, *engine_increment) = (Some(engine_clock), Some(self.increment));
+        (*opponent_time, *opponent_increment) = (Some(opponent_clock), Some(self.increment));
+        if self.moves_per_session > 0 {
+            let moves_played = position.fullmove_number - 1;
+            limits.movestogo = Some(self.moves_per_session - moves_played % self.moves_per_session);
+        }
+        limits
+    }
+}
+
+/// Reads the arguments of `level MPS BASE INC`, where BASE is in minutes or `minutes:seconds` and INC in seconds.
+pub fn parse_level(arguments: &[&str]) -> Option<(usize, Duration, Duration)> {
+    if arguments.len() != 3 {
+        return None;
+    }
+    let moves_per_session = arguments[0].parse().ok()?;
+    let base = match arguments[1].split_once(':') {
+        Some((minutes, seconds)) => Duration::from_secs(minutes.parse::<u64>().ok()? * 60 + seconds.parse::<u64>().ok()?),
+        None => Duration::try_from_secs_f64(arguments[1].parse::<f64>().ok()? * 60.0).ok()?,
+    };
+    let increment = Duration::try_from_secs_f64(arguments[2].parse::<f64>().ok()?).ok()?;
+    Some((moves_per_session, base, increment))
+}
+
+/// Reads the argument of `st SECONDS`, the time to think on every move.
+pub fn parse_st(seconds: &str) -> Option<Duration> {
+    Duration::try_from_secs_f64(seconds.parse::<f64>().ok()?).ok()
+}
+
+/// The score in centipawns from the side to move, with mates as 100000 plus the number of moves to them.
+pub fn cecp_score(evaluation: &Evaluation) -> i64 {
+    let moves_to_mate = (evaluation.score as i64 + 1) / 2;
+    match evaluation.result {
+        PositionResult::Win => MATE_SCORE + moves_to_mate,
+        PositionResult::Loss => -MATE_SCORE - moves_to_mate,
+        PositionResult::Draw => 0,
+        PositionResult::Scored => evaluation.score as i64 * 100,
+    }
+}
+
+fn print_thinking(position: &Position, info: &SearchInfo) {
+    println!(
+        "{} {} {} {} {}",
+        info.depth,
+        cecp_score(&info.evaluation),
+        info.elapsed.as_millis() / 10,
+        info.nodes,
+        position.line_to_san(&info.principal_variation).join(" "),
+    );
+}
+
+/// The result line of a game that has ended in `position`, with the reason it ended.
+pub fn result_line(position: &Position) -> String {
+    let result = game_result(position);
+    let reason = if result == "1/2-1/2" {
+        match position.draw_reason() {
+            Some(draw_reason) => draw_reason.to_string(),
+            None if position.legal_moves().is_empty() => String::from("Stalemate"),
+            None if position.board.is_insufficient_material() => String::from("Insufficient material"),
+            None => String::from("Draw"),
+        }
+    } else {
+        format!("{} mates", position.side_to_move.opposite())
+    };
+    format!("{} {{{}}}", result, reason)
+}
+
+/// Plays the move the engine found, both on the board and towards XBoard.
+fn play_engine_move(engine: &Engine, best_move: Move) {
+    let mut position = engine.position();
+    println!("move {}", position.to_uci(best_move));
+    position.play(best_move);
+    engine.continue_to(position.clone());
+    pause_search(engine);
+    if game_result(&position) != "*" {
+        println!("{}", result_line(&position));
+    }
+}
+
+/// Plays the opponent's move on the board and returns the position it leads to, or None if it is not legal.
+fn play_user_move(engine: &Engine, notation: &str) -> Option<Position> {
+    let mut position = engine.position();
+    match position.parse_move(notation) {
+        Ok(mv) => {
+            position.play(mv);
+            engine.continue_to(position.clone());
+            pause_search(engine);
+            Some(position)
+        },
+        Err(error) => {
+            log!("Illegal move {}: {}", notation, error);
+            println!("Illegal move: {}", notation);
+            None
+        },
+    }
+}
+
+/// Speaks the Chess Engine Communication Protocol, as used by XBoard and WinBoard, on `lines` until `quit` or the end
+/// of the input. The engine threads keep running afterwards, for the caller to reuse the engine or exit.
+pub fn cecp(engine: Engine, lines: impl Iterator<Item = String>) {
+    // Nothing is searched until the engine is on move.
+    pause_search(&engine);
+    engine.start_threads();
+    let post = Arc::new(AtomicBool::new(false));
+    {
+        let post = post.clone();
+        engine.on_progress(move |engine, info| if post.load(Ordering::Relaxed) { print_thinking(&engine.position(), info) });
+    }
+    engine.on_best_move(|engine, best_move| if let Some(best_move) = best_move { play_engine_move(engine, best_move) });
+    let mut force_mode = false;
+    let mut engine_color = Color::Black;
+    let mut time_control = TimeControl::default();
+    for line in lines {
+        log!("CECP command: {}", line);
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let (command, arguments) = match tokens.split_first() {
+            Some((command, arguments)) => (*command, arguments),
+            None => continue,
+        };
+        let mut start_thinking = false;
+        match command {
+            "xboard" | "accepted" | "rejected" | "random" | "hard" | "easy" | "computer" | "name" | "rating" | "ics" => {},
+            "protover" => {
+                println!(
+                    "feature myname=\"{} {}\" ping=1 setboard=1 usermove=1 san=0 time=1 draw=0 sigint=0 sigterm=0 colors=0 analyze=0 reuse=1 memory=1",
+                    env!("CARGO_PKG_NAME"),
+                    env!("CARGO_PKG_VERSION"),
+                );
+                let backends: Vec<String> = SearchBackend::ALL.iter()
+                    .map(|backend| if *backend == engine.backend() { format!("*{}", backend) } else { backend.to_string() })
+                    .collect();
+                println!("feature option=\"Backend -combo {}\"", backends.join(" /// "));
+                let options = engine.options();
+                println!("feature option=\"QuiescenceChecks -check {}\"", options.quiescence_checks as u8);
+                println!("feature option=\"NullMovePruning -check {}\"", options.null_move_pruning as u8);
+                println!("feature option=\"LateMoveReductions -check {}\"", options.late_move_reductions as u8);
+                println!("feature option=\"LmrBase -spin {} 0 1000\"", options.lmr_base);
+                println!("feature option=\"LmrDivisor -spin {} 1 1000\"", options.lmr_divisor);
+                println!("feature option=\"FutilityPruning -check {}\"", options.futility_pruning as u8);
+                println!("feature option=\"Razoring -check {}\"", options.razoring as u8);
+                println!("feature done=1");
+            },
+            "option" => match arguments.join(" ").split_once('=') {
+                Some(("Backend", value)) => match SearchBackend::from_name(value) {
+                    Some(backend) => engine.set_backend(backend),
+                    None => println!("Error (unknown backend): {}", value),
+                },
+                Some(("QuiescenceChecks", value)) => engine.update_options(|options| options.quiescence_checks = value == "1"),
+                Some(("NullMovePruning", value)) => engine.update_options(|options| options.null_move_pruning = value == "1"),
+                Some(("LateMoveReductions", value)) => engine.update_options(|options| options.late_move_reductions = value == "1"),
+                Some((name @ ("LmrBase" | "LmrDivisor"), value)) => match value.parse::<u32>() {
+                    Ok(value) if name == "LmrBase" => engine.update_options(|options| options.lmr_base = value),
+                    Ok(value) => engine.update_options(|options| options.lmr_divisor = value.max(1)),
+                    Err(_) => println!("Error (bad {}): {}", name, value),
+                },
+                Some(("FutilityPruning", value)) => engine.update_options(|options| options.futility_pruning = value == "1"),
+                Some(("Razoring", value)) => engine.update_options(|options| options.razoring = value == "1"),
+                _ => println!("Error (unknown option): {}", line),
+            },
+            "new" => {
+                engine.set_position(Position::initial());
+                engine.clear_hash();
+                pause_search(&engine);
+                force_mode = false;
+                engine_color = Color::Black;
+                time_control = TimeControl { depth: None, time_per_move: None, ..time_control };
+            },
+            "force" | "result" => {
+                engine.cancel();
+                force_mode = true;
+            },
+            "go" => {
+                force_mode = false;
+                engine_color = engine.position().side_to_move;
+                start_thinking = true;
+            },
+            "playother" => {
+                force_mode = false;
+                engine_color = engine.position().side_to_move.opposite();
+            },
+            "?" => engine.stop(),
+            "usermove" => {
+                if let Some(position) = play_user_move(&engine, arguments.first().copied().unwrap_or_default()) {
+                    start_thinking = !force_mode && position.side_to_move == engine_color && game_result(&position) == "*";
+                }
+            },
+            "undo" | "remove" => {
+                engine.cancel();
+                let mut position = engine.position();
+                let plies = if command == "remove" { 2 } else { 1 };
+                if position.history.len() >= plies {
+                    for _ in 0..plies {
+                        position.undo();
+                    }
+                    engine.set_position(position);
+                    pause_search(&engine);
+                } else {
+                    println!("Error (no moves to take back): {}", command);
+                }
+            },
+            "setboard" => {
+                match Position::from_fen(&arguments.join(" ")) {
+                    Ok(position) => {
+                        engine.set_position(position);
+                        pause_search(&engine);
+                    },
+                    Err(error) => println!("tellusererror Illegal position: {}", error),
+                }
+            },
+            "level" => match parse_level(arguments) {
+                Some((moves_per_session, base, increment)) => {
+                    time_control = TimeControl { moves_per_session, base, increment, time_per_move: None, engine_clock: None, opponent_clock: None, ..time_control };
+                },
+                None => println!("Error (bad level): {}", line),
+            },
+            "st" => match arguments.first().and_then(|seconds| parse_st(seconds)) {
+                Some(time_per_move) => time_control.time_per_move = Some(time_per_move),
+                None => println!("Error (bad st): {}", line),
+            },
+            "memory" => match arguments.first().and_then(|size_mb| size_mb.parse::<usize>().ok()) {
+                Some(size_mb) => engine.set_hash_size(size_mb),
+                None => println!("Error (bad memory): {}", line),
+            },
+            "sd" => match arguments.first().and_then(|depth| depth.parse::<usize>().ok()) {
+                Some(depth) => time_control.depth = Some(depth),
+                None => println!("Error (bad sd): {}", line),
+            },
+            "time" | "otim" => match arguments.first().and_then(|centiseconds| centiseconds.parse::<u64>().ok()) {
+                Some(centiseconds) => {
+                    let clock = Some(Duration::from_millis(centiseconds * 10));
+                    if command == "time" {
+                        time_control.engine_clock = clock;
+                    } else {
+                        time_control.opponent_clock = clock;
+                    }
+                },
+                None => println!("Error (bad clock): {}", line),
+            },
+            "post" => post.store(true, Ordering::Relaxed),
+            "nopost" => post.store(false, Ordering::Relaxed),
+            "ping" => println!("pong {}", arguments.first().copied().unwrap_or_default()),
+            "quit" => {
+                engine.cancel();
+                break;
+            },
+            // XBoard sends bare moves when it did not take up the usermove feature.
+            _ if engine.position().parse_move(command).is_ok() => {
+                if let Some(position) = play_user_move(&engine, command) {
+                    start_thinking = !force_mode && position.side_to_move == engine_color && game_result(&position) == "*";
+                }
+            },
+            _ => println!("Error (unknown command): {}", command),
+        }
+        if start_thinking {
+            let limits = time_control.search_limits(&engine.position(), engine_color);
+            engine.start(limits);
+        }
+    }
+}
@@ -1,5 +1,15 @@
 pub mod piece;
 pub mod board;
+pub mod bitboard;
 pub mod board_state;
 pub mod bitwise_operations;
-pub mod initial_board;
\ No newline at end of file
+pub mod initial_board;
+pub mod position;
+pub mod square;
+pub mod fen;
+pub mod chess_move;
+pub mod notation;
+pub mod pgn;
+pub mod perft;
+pub mod see;
+pub mod zobrist;
\ No newline at end of file
@@ -0,0 +1,378 @@
+use crate::core::chess::{board::{Board, push_pawn_move}, chess_move::*, piece::*};
+
+const A_FILE: u64 = 0x0101_0101_0101_0101;
+const B_FILE: u64 = A_FILE << 1;
+// Multiplying the a-file by this diagonal gathers its squares on the eighth rank, ready to be shifted down.
+const C7_B2_DIAGONAL: u64 = 0x0004_0810_2040_8000;
+pub const SIDE_TO_MOVE: usize = 0;
+pub const OPPONENT: usize = 1;
+
+// Attack tables, generated at compile time. Sliding pieces use kindergarten bitboards: the blockers on the line of a
+// square are multiplied onto a single rank, and the six inner ones index a table of the attacks along it.
+const KNIGHT_ATTACKS: [u64; 64] = generate_leaper_attacks(&[(2, 1), (2, -1), (-2, 1), (-2, -1), (1, 2), (1, -2), (-1, 2), (-1, -2)]);
+const KING_ATTACKS: [u64; 64] = generate_leaper_attacks(&[(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (-1, 1), (1, -1), (-1, -1)]);
+// The side to move's pawns attack towards the eighth rank and the opponent's towards the first.
+const PAWN_ATTACKS: [[u64; 64]; 2] = [generate_leaper_attacks(&[(1, 1), (1, -1)]), generate_leaper_attacks(&[(-1, 1), (-1, -1)])];
+const FIRST_RANK_ATTACKS: [[u8; 8]; 64] = generate_first_rank_attacks();
+const A_FILE_ATTACKS: [[u64; 64]; 8] = generate_a_file_attacks();
+const DIAGONAL_MASKS: [u64; 64] = generate_line_masks(1);
+const ANTI_DIAGONAL_MASKS: [u64; 64] = generate_line_masks(-1);
+
+const fn generate_leaper_attacks(offsets: &[(i8, i8)]) -> [u64; 64] {
+    let mut attacks = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let mut i = 0;
+        while i < offsets.len() {
+            let rank = (square / 8) as i8 + offsets[i].0;
+            let file = (square % 8) as i8 + offsets[i].1;
+            if 0 <= rank && rank < 8 && 0 <= file && file < 8 {
+                attacks[square] |= 1 << (rank * 8 + file);
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+    attacks
+}
+
+/// The squares a rook on `file` of the first rank attacks along it, for every occupancy of the six inner files.
+const fn generate_first_rank_attacks() -> [[u8; 8]; 64] {
+    let mut attacks = [[0; 8]; 64];
+    let mut inner_occupancy = 0;
+    while inner_occupancy < 64 {
+        let occupancy = (inner_occupancy << 1) as u8;
+        let mut file = 0;
+        while file < 8 {
+            let mut target = file + 1;
+            while target < 8 {
+                attacks[inner_occupancy][file] |= 1 << target;
+                if occupancy & (1 << target) != 0 {
+                    break;
+                }
+                target += 1;
+            }
+            let mut target = file as i8 - 1;
+            while target >= 0 {
+                attacks[inner_occupancy][file] |= 1 << target;
+                if occupancy & (1 << target) != 0 {
+                    break;
+                }
+                target -= 1;
+            }
+            file += 1;
+        }
+        inner_occupancy += 1;
+    }
+    attacks
+}
+
+/// The squares a rook on `rank` of the a-file attacks along it, indexed the way `file_attacks` gathers the blockers.
+const fn generate_a_file_attacks() -> [[u64; 64]; 8] {
+    let mut attacks = [[0; 64]; 8];
+    let mut inner_occupancy: u64 = 0;
+    while inner_occupancy < 64 {
+        let mut occupancy: u64 = 0;
+        let mut rank = 1;
+        while rank < 7 {
+            if inner_occupancy & (1 << (rank - 1)) != 0 {
+                occupancy |= 1 << (rank * 8);
+            }
+            rank += 1;
+        }
+        let index = (occupancy.wrapping_mul(C7_B2_DIAGONAL) >> 58) as usize;
+        let mut rank = 0;
+        while rank < 8 {
+            let mut target = rank + 1;
+            while target < 8 {
+                attacks[rank][index] |= 1 << (target * 8);
+                if occupancy & (1 << (target * 8)) != 0 {
+                    break;
+                }
+                target += 1;
+            }
+            let mut target = rank as i8 - 1;
+            while target >= 0 {
+                attacks[rank][index] |= 1 << (target * 8);
+                if occupancy & (1 << (target * 8)) != 0 {
+                    break;
+                }
+                target -= 1;
+            }
+            rank += 1;
+        }
+        inner_occupancy += 1;
+    }
+    attacks
+}
+
+/// The diagonal through every square, rising to the right for a `file_step` of 1 and to the left for -1, without the
+/// square itself.
+const fn generate_line_masks(file_step: i8) -> [u64; 64] {
+    let mut masks = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let mut direction = -1;
+        while direction <= 1 {
+            let mut rank = (square / 8) as i8 + direction;
+            let mut file = (square % 8) as i8 + direction * file_step;
+            while 0 <= rank && rank < 8 && 0 <= file && file < 8 {
+                masks[square] |= 1 << (rank * 8 + file);
+                rank += direction;
+                file += direction * file_step;
+            }
+            direction += 2;
+        }
+        square += 1;
+    }
+    masks
+}
+
+fn rank_attacks(square: usize, occupied: u64) -> u64 {
+    let rank_shift = square & 56;
+    let inner_occupancy = (occupied >> (rank_shift + 1)) & 63;
+    (FIRST_RANK_ATTACKS[inner_occupancy as usize][square & 7] as u64) << rank_shift
+}
+
+fn file_attacks(square: usize, occupied: u64) -> u64 {
+    let file = square & 7;
+    let occupancy = A_FILE & (occupied >> file);
+    let index = (occupancy.wrapping_mul(C7_B2_DIAGONAL) >> 58) as usize;
+    A_FILE_ATTACKS[square / 8][index] << file
+}
+
+fn line_attacks(square: usize, occupied: u64, mask: u64) -> u64 {
+    let index = ((mask & occupied).wrapping_mul(B_FILE) >> 58) as usize;
+    let attacks_on_every_rank = FIRST_RANK_ATTACKS[index][square & 7] as u64 * A_FILE;
+    attacks_on_every_rank & mask
+}
+
+pub fn rook_attacks(square: usize, occupied: u64) -> u64 {
+    rank_attacks(square, occupied) | file_attacks(square, occupied)
+}
+
+pub fn bishop_attacks(square: usize, occupied: u64) -> u64 {
+    line_attacks(square, occupied, DIAGONAL_MASKS[square]) | line_attacks(square, occupied, ANTI_DIAGONAL_MASKS[square])
+}
+
+const fn type_index(piece_type: u8) -> usize {
+    ((piece_type >> 3) - 1) as usize
+}
+
+/// Iterates over the squares of a bitboard from the lowest.
+struct Squares(u64);
+
+impl Iterator for Squares {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.0 == 0 {
+            return None;
+        }
+        let square = self.0.trailing_zeros() as usize;
+        self.0 &= self.0 - 1;
+        Some(square)
+    }
+}
+
+/// The pieces of a board as one bitboard per side and one per piece type, in the board's squares. `Board` keeps them
+/// up to date as its pieces are set, so that moves are generated without looking at every square.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Bitboards {
+    /// The side to move's pieces, then its opponent's.
+    pub sides: [u64; 2],
+    /// Indexed like `PieceArrangement`: pawns, rooks, knights, bishops, queens and kings.
+    pub types: [u64; 6],
+}
+
+impl Bitboards {
+    pub const EMPTY: Bitboards = Bitboards { sides: [0; 2], types: [0; 6] };
+
+    /// The bitboards of a board with `pieces`.
+    pub const fn from_pieces(pieces: &[u8; 64]) -> Self {
+        let mut bitboards = Bitboards::EMPTY;
+        let mut square = 0;
+        while square < 64 {
+            bitboards.toggle(square, pieces[square]);
+            square += 1;
+        }
+        bitboards
+    }
+
+    /// Puts `piece` on `square`, or takes it off if it is there already. Empty squares change nothing.
+    pub const fn toggle(&mut self, square: usize, piece: u8) {
+        if piece & PRESENCE_BITS == PRESENT {
+            let side = if piece & COLOR_BITS == WHITE { SIDE_TO_MOVE } else { OPPONENT };
+            self.sides[side] ^= 1 << square;
+            self.types[type_index(piece & TYPE_BITS)] ^= 1 << square;
+        }
+    }
+
+    /// The bitboards of the board turned around as `Board::inverted` does: square `i` becomes `63 - i`, which reverses
+    /// the bits, and the sides swap.
+    pub const fn inverted(&self) -> Self {
+        let mut types = [0; 6];
+        let mut index = 0;
+        while index < 6 {
+            types[index] = self.types[index].reverse_bits();
+            index += 1;
+        }
+        Bitboards { sides: [self.sides[OPPONENT].reverse_bits(), self.sides[SIDE_TO_MOVE].reverse_bits()], types }
+    }
+
+    pub fn occupied(&self) -> u64 {
+        self.sides[SIDE_TO_MOVE] | self.sides[OPPONENT]
+    }
+
+    pub fn pieces(&self, side: usize, piece_type: u8) -> u64 {
+        self.sides[side] & self.types[type_index(piece_type)]
+    }
+
+    /// Whether the pieces of `side` within `attackers` attack `square` when the board is occupied by `occupied`, which
+    /// lets moves be tried without playing them.
+    fn is_attacked(&self, square: usize, side: usize, occupied: u64, attackers: u64) -> bool {
+        self.attackers(square, occupied) & self.sides[side] & attackers != 0
+    }
+
+    /// The pieces of both sides that attack `square` when the board is occupied by `occupied`. Sliders are only
+    /// stopped by `occupied`, so removing the pieces in front of them brings those behind them into play.
+    pub fn attackers(&self, square: usize, occupied: u64) -> u64 {
+        let pawns = PAWN_ATTACKS[OPPONENT][square] & self.sides[SIDE_TO_MOVE] | PAWN_ATTACKS[SIDE_TO_MOVE][square] & self.sides[OPPONENT];
+        let diagonal_sliders = self.types[type_index(BISHOP)] | self.types[type_index(QUEEN)];
+        let straight_sliders = self.types[type_index(ROOK)] | self.types[type_index(QUEEN)];
+        pawns & self.types[type_index(PAWN)]
+            | KNIGHT_ATTACKS[square] & self.types[type_index(KNIGHT)]
+            | KING_ATTACKS[square] & self.types[type_index(KING)]
+            | bishop_attacks(square, occupied) & diagonal_sliders
+            | rook_attacks(square, occupied) & straight_sliders
+    }
+
+    /// Whether the opponent attacks `square`.
+    pub fn is_square_attacked(&self, square: usize) -> bool {
+        self.is_attacked(square, OPPONENT, self.occupied(), u64::MAX)
+    }
+
+    /// Whether the side to move is in check. A missing king counts as one, as for `Board::is_king_attacked`.
+    pub fn in_check(&self) -> bool {
+        let king = self.pieces(SIDE_TO_MOVE, KING);
+        king == 0 || self.is_square_attacked(king.trailing_zeros() as usize)
+    }
+
+    /// Whether `mv`, one of the pseudo-legal moves of the board, leaves the mover's king attacked.
+    pub fn leaves_king_attacked(&self, mv: &Move) -> bool {
+        let from = 1 << mv.from;
+        let to = 1 << mv.to;
+        let captured = 1 << mv.captured_square();
+        let king = if self.types[type_index(KING)] & from != 0 { to } else { self.pieces(SIDE_TO_MOVE, KING) };
+        if king == 0 {
+            return true;
+        }
+        let mut occupied = (self.occupied() & !from & !captured) | to;
+        if mv.is_castling() {
+            let (rook_from, rook_to) = mv.castling_rook_squares();
+            occupied = (occupied & !(1 << rook_from)) | 1 << rook_to;
+        }
+        self.is_attacked(king.trailing_zeros() as usize, OPPONENT, occupied, !captured)
+    }
+}
+
+impl Board {
+    /// Generates the pseudo-legal moves of the side to move from bitboards. Moves that leave the king in check are
+    /// filtered out by `legal_moves`.
+    pub fn find_moves(&self) -> Vec<Move> {
+        self.find_moves_with(self.bitboards())
+    }
+
+    /// The moves of `find_moves` that do not leave the mover's king in check.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let bitboards = self.bitboards();
+        let mut moves = self.find_moves_with(bitboards);
+        moves.retain(|mv| !bitboards.leaves_king_attacked(mv));
+        moves
+    }
+
+    /// Whether the side to move is in check.
+    pub fn in_check(&self) -> bool {
+        self.bitboards().in_check()
+    }
+
+    fn find_moves_with(&self, bitboards: &Bitboards) -> Vec<Move> {
+        let own = bitboards.sides[SIDE_TO_MOVE];
+        let opponent = bitboards.sides[OPPONENT];
+        let occupied = own | opponent;
+        let mut moves = Vec::with_capacity(64);
+        let push_targets = |from: usize, targets: u64, moves: &mut Vec<Move>| {
+            for to in Squares(targets & opponent) {
+                moves.push(Move::new(from, to, 0, CAPTURE));
+            }
+            for to in Squares(targets & !occupied) {
+                moves.push(Move::new(from, to, 0, QUIET));
+            }
+        };
+
+        for from in Squares(bitboards.pieces(SIDE_TO_MOVE, PAWN)) {
+            let rank = from / 8;
+            if rank < 7 && occupied & (1 << (from + 8)) == 0 {
+                push_pawn_move(from, from + 8, QUIET, &mut moves);
+                if rank == 1 && occupied & (1 << (from + 16)) == 0 {
+                    moves.push(Move::new(from, from + 16, 0, DOUBLE_PUSH));
+                }
+            }
+            for to in Squares(PAWN_ATTACKS[SIDE_TO_MOVE][from] & opponent) {
+                push_pawn_move(from, to, CAPTURE, &mut moves);
+            }
+            if rank == 4 {
+                for to in Squares(PAWN_ATTACKS[SIDE_TO_MOVE][from]) {
+                    if self.pieces()[to - 8] == PRESENT | BLACK | PAWN | HAS_MOVED_TWO_SQUARES {
+                        moves.push(Move::new(from, to, 0, CAPTURE | EN_PASSANT));
+                    }
+                }
+            }
+        }
+        for from in Squares(bitboards.pieces(SIDE_TO_MOVE, KNIGHT)) {
+            push_targets(from, KNIGHT_ATTACKS[from], &mut moves);
+        }
+        for from in Squares(bitboards.pieces(SIDE_TO_MOVE, BISHOP)) {
+            push_targets(from, bishop_attacks(from, occupied), &mut moves);
+        }
+        for from in Squares(bitboards.pieces(SIDE_TO_MOVE, ROOK)) {
+            push_targets(from, rook_attacks(from, occupied), &mut moves);
+        }
+        for from in Squares(bitboards.pieces(SIDE_TO_MOVE, QUEEN)) {
+            push_targets(from, rook_attacks(from, occupied) | bishop_attacks(from, occupied), &mut moves);
+        }
+        for from in Squares(bitboards.pieces(SIDE_TO_MOVE, KING)) {
+            push_targets(from, KING_ATTACKS[from], &mut moves);
+            self.find_castling_moves_with(bitboards, from, &mut moves);
+        }
+        moves
+    }
+
+    /// Castling as `find_castling_moves` generates it: the unmoved king on e1 or d1 goes two squares towards an unmoved
+    /// rook, when the squares between them are empty and neither the king nor the square it crosses are attacked.
+    fn find_castling_moves_with(&self, bitboards: &Bitboards, king_square: usize, moves: &mut Vec<Move>) {
+        let file = king_square % 8;
+        if king_square >= 8 || !(file == 3 || file == 4) || get_has_moved(self.pieces()[king_square]) {
+            return;
+        }
+        if bitboards.is_square_attacked(king_square) {
+            return;
+        }
+        let occupied = bitboards.occupied();
+        for rook_file in [0, 7] {
+            if self.pieces()[rook_file] != PRESENT | WHITE | ROOK | HAS_NOT_MOVED {
+                continue;
+            }
+            let (low, high) = if rook_file > file { (file, rook_file) } else { (rook_file, file) };
+            let between = ((1u64 << high) - 1) & !((1u64 << (low + 1)) - 1);
+            if occupied & between != 0 {
+                continue;
+            }
+            let (king_destination, rook_destination) = if rook_file > file { (file + 2, file + 1) } else { (file - 2, file - 1) };
+            if bitboards.is_square_attacked(rook_destination) {
+                continue;
+            }
+            moves.push(Move::new(file, king_destination, 0, CASTLING));
+        }
+    }
+}
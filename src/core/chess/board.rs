@@ -1,27 +1,56 @@
 use std::fmt::Display;
-use std::hash::DefaultHasher;
 use std::hash::Hash;
 use std::hash::Hasher;
 
+use crate::core::chess::bitboard::Bitboards;
 use crate::core::chess::bitwise_operations::and_byte;
 use crate::core::chess::piece::*;
 use crate::core::chess::board_state::*;
+use crate::core::chess::chess_move::*;
+use crate::core::chess::zobrist::{inverted_key, piece_key};
 use crate::core::structs::cash::Cash;
-use crate::log;
 use serde::{Serialize, Deserialize};
 use serde_big_array::BigArray;
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, Debug)]
+/// A board seen from the side to move. Its Zobrist key and its bitboards are kept up to date as pieces are set, and the
+/// key covers the side to move, castling and en passant through the colors and flags of the pieces. The key comes
+/// first so that comparing two different boards rarely has to look at their pieces, which are only written through `set`
+/// and `set_square` so that the key and the bitboards follow them.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Debug)]
+#[serde(from = "SerializedBoard", into = "SerializedBoard")]
 pub struct Board {
+    key: u64,
+    bitboards: Bitboards,
+    pieces: [u8; 64],
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct SerializedBoard {
     #[serde(with = "BigArray")]
-    pub pieces: [u8; 64],
+    pieces: [u8; 64],
+}
+
+impl From<SerializedBoard> for Board {
+    fn from(serialized_board: SerializedBoard) -> Self {
+        Board::from_pieces(serialized_board.pieces)
+    }
+}
+
+impl From<Board> for SerializedBoard {
+    fn from(board: Board) -> Self {
+        SerializedBoard { pieces: board.pieces }
+    }
+}
+
+impl Hash for Board {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.key);
+    }
 }
 
 impl Cash for Board {
-    fn cash(self: &Self) -> u64 {
-        let mut state = DefaultHasher::new();
-        self.get_board_arrangement().hash(&mut state);
-        state.finish()
+    fn cash(&self) -> u64 {
+        self.key
     }
 }
 
@@ -30,49 +59,82 @@ const KNIGHT_DIRECTIONS: [(i8, i8); 8] = [(2, 1), (2, -1), (-2, 1), (-2, -1), (1
 const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (-1, 1), (1, -1), (-1, -1)];
 const QUEEN_DIRECTIONS: [(i8, i8); 8] = [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (-1, 1), (1, -1), (-1, -1)];
 const PAWN_DIAGONALS: [(i8, i8); 2] = [(1, 1), (1, -1)];
+const PROMOTION_TYPES: [u8; 4] = [QUEEN, ROOK, BISHOP, KNIGHT];
 
 trait Coordinates<T> {
-    fn multiply(self: &Self, multiplier: i8) -> Self;
-    fn add(self: &Self, other: Self) -> Self;
-    fn as_usize(self: &Self) -> (usize, usize);
+    fn multiply(&self, multiplier: i8) -> Self;
+    fn add(&self, other: Self) -> Self;
+    fn as_usize(&self) -> (usize, usize);
 }
 
 impl Coordinates<(i8, i8)> for (i8, i8) {
-    fn multiply(self: &Self, multiplier: i8) -> Self {
+    fn multiply(&self, multiplier: i8) -> Self {
         (self.0 * multiplier, self.1 * multiplier)
     }
 
-    fn add(self: &Self, other: Self) -> Self {
+    fn add(&self, other: Self) -> Self {
         (self.0 + other.0, self.1 + other.1)
     }
 
-    fn as_usize(self: &Self) -> (usize, usize) {
+    fn as_usize(&self) -> (usize, usize) {
         (self.0 as usize, self.1 as usize)
     }
 }
 
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Board {
     pub fn new() -> Self {
-        Self { pieces: [0; 64] }
+        Self { key: 0, bitboards: Bitboards::EMPTY, pieces: [0; 64] }
     }
 
-    pub fn get(self: &Self, rank: usize, file: usize) -> u8 {
+    pub const fn from_pieces(pieces: [u8; 64]) -> Self {
+        let mut key = 0;
+        let mut square = 0;
+        while square < 64 {
+            key ^= piece_key(pieces[square], square);
+            square += 1;
+        }
+        Board { key, bitboards: Bitboards::from_pieces(&pieces), pieces }
+    }
+
+    /// The Zobrist key of the board, which identifies it among all the others but for the rarest of collisions.
+    pub fn key(&self) -> u64 {
+        self.key
+    }
+
+    pub fn bitboards(&self) -> &Bitboards {
+        &self.bitboards
+    }
+
+    pub fn pieces(&self) -> &[u8; 64] {
+        &self.pieces
+    }
+
+    pub fn get(&self, rank: usize, file: usize) -> u8 {
         self.pieces[rank*8+file]
     }
 
-    pub fn set(self: &mut Self, rank: usize, file: usize, piece: u8) {
-        self.pieces[rank*8+file] = piece
+    pub fn set(&mut self, rank: usize, file: usize, piece: u8) {
+        self.set_square(rank*8+file, piece)
     }
 
-    pub fn clone(self: &Self) -> Self {
-        let mut new_board = Board::new();
-        new_board.pieces = self.pieces.clone();
-        new_board
+    pub fn set_square(&mut self, square: usize, piece: u8) {
+        self.key ^= piece_key(self.pieces[square], square) ^ piece_key(piece, square);
+        self.bitboards.toggle(square, self.pieces[square]);
+        self.bitboards.toggle(square, piece);
+        self.pieces[square] = piece;
     }
 
-    pub fn inverted(self: &Self) -> Self {
+    pub fn inverted(&self) -> Self {
         // Board{pieces: xor_byte(self.pieces, COLOR_BITS)}
         let mut new_board = Board::new();
+        new_board.key = inverted_key(self.key);
+        new_board.bitboards = self.bitboards.inverted();
         for i in 0..64 {
             if get_presence(self.pieces[i]) == PRESENT {
                 new_board.pieces[63-i] = negate_color(self.pieces[i]);
@@ -83,80 +145,150 @@ impl Board {
         return new_board;
     }
 
-    pub fn normalize_opponent_pieces(self: &mut Self) {
+    /// Clears the en passant flag of the opponent's pawns, returning the square of the pawn that had it.
+    pub fn normalize_opponent_pieces(&mut self) -> Option<u8> {
+        let mut cleared = None;
         for i in 0..64 {
             if self.pieces[i] == PRESENT | BLACK | PAWN | HAS_MOVED_TWO_SQUARES {
-                self.pieces[i] = PRESENT | BLACK | PAWN | HAS_NOT_MOVED_TWO_SQUARES
+                self.set_square(i, PRESENT | BLACK | PAWN | HAS_NOT_MOVED_TWO_SQUARES);
+                cleared = Some(i as u8);
+            }
+        }
+        cleared
+    }
+
+    /// Passes the turn, turning the board around as `make_move` does. The opponent's pawns that could have been taken
+    /// en passant no longer can.
+    pub fn null_move(&self) -> Board {
+        let mut board = *self;
+        board.normalize_opponent_pieces();
+        board.inverted()
+    }
+
+    /// Plays `mv` and turns the board around, so that it is seen from the opponent's side afterwards.
+    pub fn make_move(&mut self, mv: Move) -> Undo {
+        let from = mv.from as usize;
+        let to = mv.to as usize;
+        let captured_square = mv.captured_square();
+        let piece = self.pieces[from];
+        let undo = Undo {
+            key: self.key,
+            bitboards: self.bitboards,
+            moved_piece: piece,
+            captured_piece: self.pieces[captured_square],
+            corner_pieces: [self.pieces[0], self.pieces[7]],
+            cleared_en_passant: None,
+        };
+
+        self.set_square(captured_square, EMPTY);
+        self.set_square(from, EMPTY);
+        self.set_square(to, if mv.is_promotion() {
+            // Promoted rooks can never castle.
+            let moved_flag = if mv.promotion == ROOK { HAS_MOVED } else { HAS_NOT_MOVED };
+            PRESENT | WHITE | mv.promotion | moved_flag
+        } else {
+            match get_type(piece) {
+                // Only flag the pawn when it can actually be taken en passant, so that the flag doubles as the
+                // en passant right in the identity of the position.
+                PAWN if mv.is_double_push() && self.has_adjacent_opponent_pawn(to / 8, to % 8) => PRESENT | WHITE | PAWN | HAS_MOVED_TWO_SQUARES,
+                PAWN => PRESENT | WHITE | PAWN | HAS_NOT_MOVED_TWO_SQUARES,
+                // Kings and rooks remember that they have moved so that castling rights are lost for good.
+                ROOK | KING => PRESENT | WHITE | get_type(piece) | HAS_MOVED,
+                piece_type => PRESENT | WHITE | piece_type,
             }
+        });
+        if mv.is_castling() {
+            let (rook_from, rook_to) = mv.castling_rook_squares();
+            self.set_square(rook_from, EMPTY);
+            self.set_square(rook_to, PRESENT | WHITE | ROOK | HAS_MOVED);
+        }
+        if get_type(piece) == KING {
+            self.mark_rooks_as_moved();
+        }
+        // The opponent's en passant right expires with our move, so it has to be cleared before the board is handed over.
+        let cleared_en_passant = self.normalize_opponent_pieces();
+        *self = self.inverted();
+        Undo { cleared_en_passant, ..undo }
+    }
+
+    /// Takes back `mv`, which has to be the last move made on this board with `undo` being what `make_move` returned.
+    pub fn unmake_move(&mut self, mv: Move, undo: Undo) {
+        *self = self.inverted();
+        if let Some(square) = undo.cleared_en_passant {
+            self.pieces[square as usize] = PRESENT | BLACK | PAWN | HAS_MOVED_TWO_SQUARES;
         }
+        if mv.is_castling() {
+            let (_, rook_to) = mv.castling_rook_squares();
+            self.pieces[rook_to] = EMPTY;
+        }
+        self.pieces[mv.to as usize] = EMPTY;
+        self.pieces[mv.captured_square()] = undo.captured_piece;
+        self.pieces[mv.from as usize] = undo.moved_piece;
+        self.pieces[0] = undo.corner_pieces[0];
+        self.pieces[7] = undo.corner_pieces[1];
+        self.key = undo.key;
+        self.bitboards = undo.bitboards;
+    }
+
+    /// The board after `mv`, seen from the opponent's side.
+    pub fn next_board(&self, mv: Move) -> Board {
+        let mut next_board = *self;
+        next_board.make_move(mv);
+        next_board
     }
 
+    /// Generates the pseudo-legal moves of the side to move square by square. `find_moves` generates the same moves
+    /// from bitboards much faster, so this is only kept as the reference it is tested against.
     #[inline(never)]
-    pub fn find_moves(self: &Self) -> Vec<Board> {
-        let presence_board = Board {pieces: and_byte(self.pieces, PRESENCE_BITS)};
-        let color_board = Board {pieces: and_byte(self.pieces, COLOR_BITS)};
-        let type_board = Board {pieces: and_byte(self.pieces, TYPE_BITS)};
+    pub fn find_moves_mailbox(&self) -> Vec<Move> {
+        let presence_board = and_byte(self.pieces, PRESENCE_BITS);
+        let color_board = and_byte(self.pieces, COLOR_BITS);
+        let type_board = and_byte(self.pieces, TYPE_BITS);
         let mut vec_length: usize = 0;
         for i in 0..64 {
-            if presence_board.pieces[i] == EMPTY || color_board.pieces[i] == BLACK {
+            if presence_board[i] == EMPTY || color_board[i] == BLACK {
                 continue;
             }
-            vec_length +=  get_max_movement(type_board.pieces[i]);
+            vec_length +=  get_max_movement(type_board[i]);
         }
         let mut moves = Vec::with_capacity(vec_length);
         for i in 0..64 {
-            if presence_board.pieces[i] == EMPTY || color_board.pieces[i] == BLACK {
+            if presence_board[i] == EMPTY || color_board[i] == BLACK {
                 continue;
             }
-            // if get_presence(piece) == EMPTY || get_color(piece) == BLACK {
-            //     continue;
-            // }
             let rank = i / 8;
             let file = i % 8;
-            // match type_board {
-            //     Some(_) => {}
-            //     None => {
-            //         type_board = Some(Board {pieces: and_byte(self.pieces, TYPE_BITS)});
-            //     }
-            // };
-            match type_board.pieces[i] {
-            // match get_type(source_piece) {
+            match type_board[i] {
                 PAWN => {
-                    if rank<7 && presence_board.get(rank+1, file) == EMPTY {
-                        let mut new_board = self.clone();
-                        new_board.set(rank, file, EMPTY);
-                        new_board.set(rank+1, file, PRESENT | WHITE | PAWN | HAS_NOT_MOVED_TWO_SQUARES);
-                        moves.push(new_board);
-
-                        if rank<6 && presence_board.get(rank+2, file) == EMPTY {
-                            let mut new_board = self.clone();
-                            new_board.set(rank, file, EMPTY);
-                            new_board.set(rank+2, file, PRESENT | WHITE | PAWN | HAS_MOVED_TWO_SQUARES);
-                            moves.push(new_board);
+                    if rank<7 && presence_board[(rank+1)*8+file] == EMPTY {
+                        push_pawn_move(i, i+8, QUIET, &mut moves);
+
+                        if rank == 1 && presence_board[(rank+2)*8+file] == EMPTY {
+                            moves.push(Move::new(i, i+16, 0, DOUBLE_PUSH));
                         }
                     }
                     for diagonal in PAWN_DIAGONALS {
                         let destination: (i8, i8) = diagonal.add((rank as i8, file as i8));
                         if (0<=destination.0) && (destination.0<8) && 0<=destination.1 && destination.1<8 {
                             let destination = destination.as_usize();
-                            let target_piece_presence = presence_board.get(destination.0, destination.1);
-                            let target_piece_color = color_board.get(destination.0, destination.1);
+                            let target_piece_presence = presence_board[destination.0*8+destination.1];
+                            let target_piece_color = color_board[destination.0*8+destination.1];
                             if target_piece_presence == PRESENT && target_piece_color == BLACK {
-                                let mut new_board = self.clone();
-                                new_board.set(rank, file, EMPTY);
-                                new_board.set(destination.0, destination.1, PRESENT | WHITE | PAWN | HAS_NOT_MOVED_TWO_SQUARES);
-                                moves.push(new_board);
+                                push_pawn_move(i, destination.0*8+destination.1, CAPTURE, &mut moves);
+                            }
+                            if rank == 4 && self.get(rank, destination.1) == PRESENT | BLACK | PAWN | HAS_MOVED_TWO_SQUARES {
+                                moves.push(Move::new(i, destination.0*8+destination.1, 0, CAPTURE | EN_PASSANT));
                             }
                         }
                     }
                 },
                 ROOK | BISHOP | QUEEN | KNIGHT | KING => {
-                    let max_distance: i8 = match type_board.pieces[i] {
+                    let max_distance: i8 = match type_board[i] {
                         ROOK | BISHOP | QUEEN => 8,
                         KNIGHT | KING => 2,
                         _ => panic!("Not a valid type")
                     };
-                    let directions = match type_board.pieces[i] {
+                    let directions = match type_board[i] {
                         ROOK => &ROOK_DIRECTIONS[..],
                         BISHOP => &BISHOP_DIRECTIONS[..],
                         QUEEN | KING => &QUEEN_DIRECTIONS[..],
@@ -171,24 +303,14 @@ impl Board {
                                 let destination: (i8, i8) = direction.multiply(distance).add((rank as i8, file as i8));
                                 if (0<=destination.0) && (destination.0<8) && 0<=destination.1 && destination.1<8 {
                                     let destination = destination.as_usize();
-                                    let piece_presence = presence_board.get(destination.0, destination.1);
-                                    let piece_color = color_board.get(destination.0, destination.1);
+                                    let piece_presence = presence_board[destination.0*8+destination.1];
+                                    let piece_color = color_board[destination.0*8+destination.1];
                                     if piece_presence == EMPTY {
-                                        if type_board.pieces[i] == KNIGHT {
-                                        }
-                                        let mut new_board = self.clone();
-                                        new_board.set(rank, file, EMPTY);
-                                        new_board.set(destination.0, destination.1, PRESENT | WHITE | type_board.pieces[i]);
-                                        moves.push(new_board);
+                                        moves.push(Move::new(i, destination.0*8+destination.1, 0, QUIET));
                                     } else {
                                         can_move_in_directions[direction_idx] = false;
                                         if piece_color == BLACK {
-                                            if type_board.pieces[i] == KNIGHT {
-                                            }
-                                            let mut new_board = self.clone();
-                                            new_board.set(rank, file, EMPTY);
-                                            new_board.set(destination.0, destination.1, PRESENT | WHITE | type_board.pieces[i]);
-                                            moves.push(new_board);
+                                            moves.push(Move::new(i, destination.0*8+destination.1, 0, CAPTURE));
                                         }
                                     }
                                 } else {
@@ -197,77 +319,189 @@ impl Board {
                             }
                         }
                     }
+                    if type_board[i] == KING {
+                        self.find_castling_moves(rank, file, &mut moves);
+                    }
                 },
                 _ => panic!("Invalid piece type"),
             }
         }
-        moves.iter_mut().for_each(|board| {
-            let mut new_board = board.clone();
-            new_board = new_board.inverted();
-            new_board.normalize_opponent_pieces();
-            *board = new_board
-        });
         moves.shrink_to_fit();
         moves
     }
 
-    fn is_opponent_in_check(self: &Self) -> bool {
-        let mut king_rank: i8 = 8;
-        let mut king_file: i8 = 8;
-        for rank in 0..8 {
-            for file in 0..8 {
-                let piece = self.get(rank, file);
-                if get_presence(piece) == PRESENT && get_color(piece) == BLACK && get_type(piece) == KING {
-                    king_rank = rank as i8;
-                    king_file = file as i8;
-                    break;
-                }
+    fn has_adjacent_opponent_pawn(&self, rank: usize, file: usize) -> bool {
+        let opponent_pawn = |file: usize| {
+            let piece = self.get(rank, file);
+            get_presence(piece) == PRESENT && get_color(piece) == BLACK && get_type(piece) == PAWN
+        };
+        (file > 0 && opponent_pawn(file-1)) || (file < 7 && opponent_pawn(file+1))
+    }
+
+    /// Castling is generated relative to the side to move. As the board is rotated rather than mirrored on every ply,
+    /// the unmoved king sits on e1 for white and on d1 for black, so the rook can be on either side of it.
+    /// The king always travels two squares towards the rook and the rook lands on the square the king crossed.
+    fn find_castling_moves(&self, rank: usize, file: usize, moves: &mut Vec<Move>) {
+        let king = self.get(rank, file);
+        if rank != 0 || !(file == 3 || file == 4) || get_has_moved(king) {
+            return;
+        }
+        if self.is_king_attacked(WHITE) {
+            return;
+        }
+        for rook_file in [0, 7] {
+            if self.get(0, rook_file) != PRESENT | WHITE | ROOK | HAS_NOT_MOVED {
+                continue;
+            }
+            let direction: i8 = if rook_file > file { 1 } else { -1 };
+            let (low, high) = if rook_file > file { (file, rook_file) } else { (rook_file, file) };
+            if (low+1..high).any(|between| get_presence(self.get(0, between)) == PRESENT) {
+                continue;
             }
+            let king_destination = (file as i8 + 2*direction) as usize;
+            let rook_destination = (file as i8 + direction) as usize;
+
+            if self.is_square_attacked(rook_destination, BLACK) {
+                continue;
+            }
+
+            moves.push(Move::new(file, king_destination, 0, CASTLING));
         }
-        if king_rank == 8 || king_file == 8 {
-            return true;
+    }
+
+    /// Once the king has moved, none of its rooks can castle any more. Marking them keeps positions with the same
+    /// castling rights identical. Unmoved rooks can only stand in the corners.
+    fn mark_rooks_as_moved(&mut self) {
+        for i in [0, 7] {
+            if get_presence(self.pieces[i]) == PRESENT && get_color(self.pieces[i]) == WHITE && get_type(self.pieces[i]) == ROOK {
+                self.set_square(i, self.pieces[i] | HAS_MOVED);
+            }
         }
+    }
+
+    pub fn find_king(&self, color: u8) -> Option<usize> {
+        for i in 0..64 {
+            let piece = self.pieces[i];
+            if get_presence(piece) == PRESENT && get_color(piece) == color && get_type(piece) == KING {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    /// Returns whether any piece of `by_color` attacks `square` (`rank*8+file`). White pawns attack towards rank 8
+    /// and black pawns towards rank 1, which holds for both orientations of the board.
+    pub fn is_square_attacked(&self, square: usize, by_color: u8) -> bool {
+        let target: (i8, i8) = ((square / 8) as i8, (square % 8) as i8);
+        let attacker_at = |offset: (i8, i8), types: &[u8]| -> Option<bool> {
+            let source = offset.add(target);
+            if !((0<=source.0) && (source.0<8) && (0<=source.1) && (source.1<8)) {
+                return None;
+            }
+            let source = source.as_usize();
+            let piece = self.get(source.0, source.1);
+            if get_presence(piece) == EMPTY {
+                return Some(false);
+            }
+            Some(get_color(piece) == by_color && types.contains(&get_type(piece)))
+        };
 
+        let pawn_rank_offset: i8 = if by_color == WHITE { -1 } else { 1 };
+        for file_offset in [-1, 1] {
+            if attacker_at((pawn_rank_offset, file_offset), &[PAWN]) == Some(true) {
+                return true;
+            }
+        }
+        for direction in KNIGHT_DIRECTIONS {
+            if attacker_at(direction, &[KNIGHT]) == Some(true) {
+                return true;
+            }
+        }
         for direction in QUEEN_DIRECTIONS {
-            for distance in 1..8 {
-                let destination: (i8, i8) = direction.multiply(distance).add((king_rank, king_file));
-                if direction.0 == 0 || direction.1 == 0 {
-                    if (0<=destination.0) && (destination.0<8) && (0<=destination.1) && (destination.1<8) {
-                        let destination = destination.as_usize();
-                        let piece = self.get(destination.0, destination.1);
-                        if get_presence(piece) == PRESENT {
-                            if get_color(piece) == WHITE {
-                                if ROOK_DIRECTIONS.contains(&direction) && get_type(piece) == ROOK {
-                                    return true;
-                                } else if BISHOP_DIRECTIONS.contains(&direction) && get_type(piece) == BISHOP {
-                                    return true;
-                                } else if get_type(piece) == QUEEN {
-                                    return true;
-                                }
-                            } else {
-                                break;
-                            }
+            if attacker_at(direction, &[KING]) == Some(true) {
+                return true;
+            }
+        }
+        for (directions, types) in [(&ROOK_DIRECTIONS, [ROOK, QUEEN]), (&BISHOP_DIRECTIONS, [BISHOP, QUEEN])] {
+            for direction in directions {
+                for distance in 1..8 {
+                    let source = direction.multiply(distance).add(target);
+                    if !((0<=source.0) && (source.0<8) && (0<=source.1) && (source.1<8)) {
+                        break;
+                    }
+                    let source = source.as_usize();
+                    let piece = self.get(source.0, source.1);
+                    if get_presence(piece) == PRESENT {
+                        if get_color(piece) == by_color && types.contains(&get_type(piece)) {
+                            return true;
                         }
-                    } else {
                         break;
                     }
                 }
             }
         }
-        return false;
+        false
     }
 
-    pub fn get_evaluation(self: &Self) -> (Evaluation, Box<[Board]>) {
-        let moves = self.find_moves();
-        let mut legal_moves: Vec<Board> = vec![];
-        for board in moves.iter() {
-            if !board.is_opponent_in_check() {
-                legal_moves.push(*board);
+    /// A missing king is treated as being in check so that boards where it was captured are never accepted.
+    pub fn is_king_attacked(&self, color: u8) -> bool {
+        match self.find_king(color) {
+            Some(square) => self.is_square_attacked(square, negate_color(color)),
+            None => true,
+        }
+    }
+
+    /// Dead positions where neither side can ever mate: bare kings, a single minor piece, or bishops that all stand on
+    /// squares of the same color.
+    pub fn is_insufficient_material(&self) -> bool {
+        let board_arrangement = self.get_board_arrangement();
+        let sides = [&board_arrangement.higher, &board_arrangement.lower];
+        if sides.iter().any(|side| side.count(PAWN) > 0 || side.count(ROOK) > 0 || side.count(QUEEN) > 0) {
+            return false;
+        }
+        let knights: u8 = sides.iter().map(|side| side.count(KNIGHT)).sum();
+        let bishops: u8 = sides.iter().map(|side| side.count(BISHOP)).sum();
+        if knights + bishops <= 1 {
+            return true;
+        }
+        if knights > 0 {
+            return false;
+        }
+        let mut bishop_square_colors = (0..64)
+            .filter(|i| get_presence(self.pieces[*i]) == PRESENT && get_type(self.pieces[*i]) == BISHOP)
+            .map(|i| (i / 8 + i % 8) % 2);
+        let first_square_color = bishop_square_colors.next();
+        bishop_square_colors.all(|square_color| Some(square_color) == first_square_color)
+    }
+
+    /// The moves of `find_moves_mailbox` that do not leave the mover's king in check, found by playing each of them.
+    pub fn legal_moves_mailbox(&self) -> Vec<Move> {
+        let mut legal_moves: Vec<Move> = vec![];
+        let mut board = *self;
+        for mv in self.find_moves_mailbox() {
+            let undo = board.make_move(mv);
+            // Successors are seen from the opponent's side, so the side that just moved is black.
+            if !board.is_king_attacked(BLACK) {
+                legal_moves.push(mv);
             }
+            board.unmake_move(mv, undo);
         }
+        legal_moves
+    }
+
+    pub fn get_evaluation(&self) -> (Evaluation, Box<[Move]>) {
+        if self.is_insufficient_material() {
+            return (
+                Evaluation{
+                    result: PositionResult::Draw,
+                    score: 0,
+                },
+                Box::new([]),
+            );
+        }
+        let legal_moves = self.legal_moves();
         if legal_moves.len() == 0 {
-            let inverted_board = self.inverted();
-            if inverted_board.is_opponent_in_check() {
+            if self.in_check() {
                 return (Evaluation{
                         result: PositionResult::Loss,
                         score: 0,
@@ -313,18 +547,30 @@ impl Board {
     //     black_major: &mut [u8; 6],
     // )
 
-    pub fn get_board_arrangement(self: &Self) -> BoardArrangement {
+    /// The side to move's pieces, counted as in `get_board_arrangement`.
+    pub fn side_to_move_arrangement(&self) -> PieceArrangement {
+        let mut arrangement = PieceArrangement { pawns: 0, major_pieces: [0; 6] };
+        for (square, piece) in self.pieces.iter().enumerate() {
+            if get_presence(*piece) == PRESENT && get_color(*piece) == WHITE {
+                if get_type(*piece) == PAWN {
+                    arrangement.pawns |= 1 << square;
+                }
+                arrangement.major_pieces[(get_type(*piece) >> 3) as usize - 1] += 1;
+            }
+        }
+        arrangement
+    }
+
+    pub fn get_board_arrangement(&self) -> BoardArrangement {
         let mut white_pawns: u64 = 0;
         let mut white_major: [u8; 6] = [0; 6];
         let mut black_pawns: u64 = 0;
         let mut black_major: [u8; 6] = [0; 6];
-        let type_board = Board {pieces: and_byte(self.pieces, TYPE_BITS)};
-        for i in 0..64 {
-            let piece = self.pieces[i];
+        for (i, &piece) in self.pieces.iter().enumerate() {
             if get_presence(piece) == EMPTY {
                 continue;
             } else {
-                let piece_type = type_board.pieces[i];
+                let piece_type = get_type(piece);
                 if piece_type == PAWN {
                     if get_color(piece) == WHITE {
                         white_pawns = white_pawns | (1 << i);
@@ -397,10 +643,22 @@ impl Board {
     }
 }
 
+/// Moves a pawn without a double step, promoting it to every possible piece when it reaches the last rank.
+pub(crate) fn push_pawn_move(from: usize, to: usize, flags: u8, moves: &mut Vec<Move>) {
+    if to / 8 == 7 {
+        for promotion in PROMOTION_TYPES {
+            moves.push(Move::new(from, to, promotion, flags));
+        }
+    } else {
+        moves.push(Move::new(from, to, 0, flags));
+    }
+}
+
 pub fn can_come_after(source: &PieceArrangement, destination: &PieceArrangement) -> bool {
+    // Every piece beyond the ones already on the board has to be a promoted pawn.
     let mut extra_pieces = 0;
     for i in 0..6 {
-        if i == (PAWN >> 3) as usize {
+        if i == (PAWN >> 3) as usize - 1 {
             continue;
         }
         if destination.major_pieces[i] > source.major_pieces[i] {
@@ -420,6 +678,9 @@ pub fn can_come_after(source: &PieceArrangement, destination: &PieceArrangement)
 }
 
 fn match_pawns(source: Vec<u8>, destination: Vec<u8>) -> bool {
+    if destination.is_empty() {
+        return true;
+    }
     let destination_index = 0;
     let destination_pawn = destination[destination_index];
     let destination_pawn_rank = destination_pawn / 8;
@@ -480,6 +741,12 @@ pub struct BoardArrangement {
     pub lower: PieceArrangement,
 }
 
+impl Default for BoardArrangement {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BoardArrangement {
     pub fn new() -> Self {
         BoardArrangement {
@@ -501,7 +768,7 @@ pub fn can_come_after_board_arrangement(source: &BoardArrangement, destination:
 
 impl Display for BoardArrangement {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut board = Board { pieces: [0; 64] };
+        let mut board = Board::new();
         for i in 0..64 {
             if 1<<i & self.higher.pawns != 0 {
                 board.set(i / 8, i % 8, PRESENT | WHITE | PAWN | HAS_NOT_MOVED_TWO_SQUARES);
@@ -520,6 +787,17 @@ pub struct PieceArrangement {
     major_pieces: [u8; 6],
 }
 
+impl PieceArrangement {
+    pub fn count(&self, piece_type: u8) -> u8 {
+        self.major_pieces[(piece_type >> 3) as usize - 1]
+    }
+
+    /// Whether the side has nothing but its king and pawns, where zugzwang is common.
+    pub fn has_only_king_and_pawns(&self) -> bool {
+        [ROOK, KNIGHT, BISHOP, QUEEN].iter().all(|piece_type| self.count(*piece_type) == 0)
+    }
+}
+
 impl Display for Board {
     
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
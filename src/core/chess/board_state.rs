@@ -1,6 +1,6 @@
 use std::{cmp::Ordering, collections::HashSet, fmt::Display, sync::RwLock};
 
-use crate::core::{chess::board::*, engine::structs::TimestampedEvaluation};
+use crate::core::{chess::{board::*, chess_move::Move}, engine::structs::TimestampedEvaluation};
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum PositionResult {
@@ -35,7 +35,7 @@ impl Display for Evaluation {
 }
 
 impl Evaluation {
-    pub fn compare_to(self: &Self, other: &Self) -> Ordering {
+    pub fn compare_to(&self, other: &Self) -> Ordering {
         match (self.result, other.result) {
             (PositionResult::Win, PositionResult::Win) => self.score.cmp(&other.score),
             (PositionResult::Win, PositionResult::Scored) => Ordering::Greater,
@@ -56,7 +56,7 @@ impl Evaluation {
         }
     }
 
-    pub fn invert(self: &Self) -> Self {
+    pub fn invert(&self) -> Self {
         match self.result {
             PositionResult::Win => Evaluation{result: PositionResult::Loss, score: self.score+1},
             PositionResult::Scored => Evaluation{result: PositionResult::Scored, score: -self.score},
@@ -77,19 +77,29 @@ impl Evaluation {
 
 pub struct BoardState {
 	pub self_evaluation: Evaluation,
-	pub next_moves: Box<[(Board, Option<TimestampedEvaluation>)]>,
+	pub next_moves: Box<[(Move, Option<TimestampedEvaluation>)]>,
 
-	pub previous_moves: RwLock<HashSet<Board>>,
+	/// The positions this one was reached from, with the move played in each of them.
+	pub previous_moves: RwLock<HashSet<(Board, Move)>>,
+
+	/// Next moves that repeat a position of the game and are therefore scored as draws.
+	pub repeated_moves: RwLock<HashSet<Move>>,
 
 	pub next_best_move: RwLock<Option<NextBestMove>>,
 }
 
 #[derive(Clone, Copy, PartialEq)]
 pub struct NextBestMove {
-	pub board: Board,
+	pub mv: Move,
 	pub evaluation: Evaluation,
 }
 
+impl Default for BoardState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BoardState {
     pub fn new() -> Self {
         BoardState {
@@ -98,6 +108,8 @@ impl BoardState {
 
             previous_moves: RwLock::new(HashSet::new()),
 
+            repeated_moves: RwLock::new(HashSet::new()),
+
             next_best_move: RwLock::new(None),
         }
     }
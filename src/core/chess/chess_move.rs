@@ -0,0 +1,85 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::chess::{bitboard::Bitboards, piece::*, square::square_name};
+
+pub const QUIET: u8 = 0b0;
+pub const CAPTURE: u8 = 0b1;
+pub const DOUBLE_PUSH: u8 = 0b1 << 1;
+pub const EN_PASSANT: u8 = 0b1 << 2;
+pub const CASTLING: u8 = 0b1 << 3;
+
+/// A move on a board seen from the side to move. `from` and `to` are squares of that board (`rank*8+file`), so
+/// the same move has different squares for White and Black. `promotion` is the piece type a pawn turns into, or 0.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, Debug)]
+pub struct Move {
+    pub from: u8,
+    pub to: u8,
+    pub promotion: u8,
+    pub flags: u8,
+}
+
+impl Move {
+    pub fn new(from: usize, to: usize, promotion: u8, flags: u8) -> Self {
+        Move { from: from as u8, to: to as u8, promotion, flags }
+    }
+
+    pub fn is_capture(&self) -> bool {
+        self.flags & CAPTURE != 0
+    }
+
+    pub fn is_double_push(&self) -> bool {
+        self.flags & DOUBLE_PUSH != 0
+    }
+
+    pub fn is_en_passant(&self) -> bool {
+        self.flags & EN_PASSANT != 0
+    }
+
+    pub fn is_castling(&self) -> bool {
+        self.flags & CASTLING != 0
+    }
+
+    pub fn is_promotion(&self) -> bool {
+        self.promotion != 0
+    }
+
+    /// The square the captured piece stands on, which is beside the destination for en passant.
+    pub fn captured_square(&self) -> usize {
+        if self.is_en_passant() {
+            (self.from as usize / 8) * 8 + self.to as usize % 8
+        } else {
+            self.to as usize
+        }
+    }
+
+    /// The squares the rook moves between when castling. The king always moves two squares towards the rook.
+    pub fn castling_rook_squares(&self) -> (usize, usize) {
+        let (from, to) = (self.from as usize, self.to as usize);
+        if to > from {
+            (from - from % 8 + 7, from + 1)
+        } else {
+            (from - from % 8, from - 1)
+        }
+    }
+}
+
+/// Written in the coordinates of the board the move was generated on.
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let promotion = if self.is_promotion() { get_type_string(self.promotion).to_lowercase() } else { String::new() };
+        write!(f, "{}{}{}", square_name(self.from as usize), square_name(self.to as usize), promotion)
+    }
+}
+
+/// What `Board::make_move` needs to remember for `Board::unmake_move` to restore the board.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Undo {
+    pub key: u64,
+    pub bitboards: Bitboards,
+    pub moved_piece: u8,
+    pub captured_piece: u8,
+    pub corner_pieces: [u8; 2],
+    pub cleared_en_passant: Option<u8>,
+}
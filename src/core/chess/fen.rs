@@ -0,0 +1,247 @@
+use std::fmt::Display;
+
+use crate::core::chess::{board::Board, piece::*, position::{Color, Position}, square::{parse_square, square_name}};
+
+pub const INITIAL_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum FenError {
+    WrongFieldCount(usize),
+    WrongRankCount(usize),
+    InvalidPiece(char),
+    WrongRankLength { rank: usize, length: usize },
+    InvalidSideToMove(String),
+    InvalidCastling(String),
+    UnavailableCastling(char),
+    InvalidEnPassant(String),
+    InvalidClock(String),
+    WrongKingCount { color: Color, count: usize },
+    PawnOnBackRank(String),
+    OpponentInCheck(Color),
+}
+
+impl Display for FenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FenError::WrongFieldCount(count) => write!(f, "expected 6 space separated fields (or 4 without the clocks), found {}", count),
+            FenError::WrongRankCount(count) => write!(f, "expected 8 ranks separated by '/', found {}", count),
+            FenError::InvalidPiece(c) => write!(f, "'{}' is not a piece, expected one of pnbrqkPNBRQK or a digit from 1 to 8", c),
+            FenError::WrongRankLength { rank, length } => write!(f, "rank {} describes {} squares instead of 8", rank, length),
+            FenError::InvalidSideToMove(side) => write!(f, "side to move must be 'w' or 'b', found '{}'", side),
+            FenError::InvalidCastling(castling) => write!(f, "castling rights must be '-' or a combination of KQkq, found '{}'", castling),
+            FenError::UnavailableCastling(right) => write!(f, "castling right '{}' needs the king and the rook on their original squares", right),
+            FenError::InvalidEnPassant(square) => write!(f, "'{}' is not a valid en passant square for this position", square),
+            FenError::InvalidClock(clock) => write!(f, "'{}' is not a valid move counter", clock),
+            FenError::WrongKingCount { color, count } => write!(f, "{} has {} kings instead of 1", color, count),
+            FenError::PawnOnBackRank(square) => write!(f, "there is a pawn on {}, pawns cannot stand on the first or last rank", square),
+            FenError::OpponentInCheck(color) => write!(f, "{} is in check although it is not to move", color),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+fn parse_piece(c: char) -> Option<u8> {
+    let piece_type = match c.to_ascii_lowercase() {
+        'p' => PAWN,
+        'n' => KNIGHT,
+        'b' => BISHOP,
+        'r' => ROOK,
+        'q' => QUEEN,
+        'k' => KING,
+        _ => return None,
+    };
+    let color = if c.is_ascii_uppercase() { WHITE } else { BLACK };
+    Some(PRESENT | color | piece_type)
+}
+
+fn piece_char(piece: u8) -> char {
+    let c = get_type_string(piece).chars().next().unwrap();
+    if get_color(piece) == WHITE { c } else { c.to_ascii_lowercase() }
+}
+
+impl Position {
+    /// Parses a position in Forsyth-Edwards Notation. The clocks may be left out, in which case they start at 0 and 1.
+    /// An en passant square is only kept when a pawn can actually capture on it, like the engine does for its own moves.
+    pub fn from_fen(fen: &str) -> Result<Position, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 6 && fields.len() != 4 {
+            return Err(FenError::WrongFieldCount(fields.len()));
+        }
+
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != 8 {
+            return Err(FenError::WrongRankCount(ranks.len()));
+        }
+        let mut board = Board::new();
+        for (index, rank_description) in ranks.iter().enumerate() {
+            let rank = 7 - index;
+            let mut file = 0;
+            for c in rank_description.chars() {
+                if let Some(empty_squares) = c.to_digit(10).filter(|digit| (1..=8).contains(digit)) {
+                    file += empty_squares as usize;
+                    continue;
+                }
+                let piece = parse_piece(c).ok_or(FenError::InvalidPiece(c))?;
+                if file < 8 {
+                    if get_type(piece) == PAWN && (rank == 0 || rank == 7) {
+                        return Err(FenError::PawnOnBackRank(square_name(rank*8+file)));
+                    }
+                    board.set(rank, file, piece);
+                }
+                file += 1;
+            }
+            if file != 8 {
+                return Err(FenError::WrongRankLength { rank: rank + 1, length: file });
+            }
+        }
+        for (color, color_bits) in [(Color::White, WHITE), (Color::Black, BLACK)] {
+            let count = board.pieces().iter().filter(|piece| **piece == PRESENT | color_bits | KING).count();
+            if count != 1 {
+                return Err(FenError::WrongKingCount { color, count });
+            }
+        }
+
+        let side_to_move = match fields[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            side => return Err(FenError::InvalidSideToMove(side.to_string())),
+        };
+
+        // Castling rights live in the pieces: every king and rook that cannot castle any more is marked as moved.
+        let mut castling_squares: Vec<(usize, usize)> = vec![];
+        if fields[2] != "-" {
+            for right in fields[2].chars() {
+                let (king_square, rook_square, color) = match right {
+                    'K' => (4, 7, WHITE),
+                    'Q' => (4, 0, WHITE),
+                    'k' => (60, 63, BLACK),
+                    'q' => (60, 56, BLACK),
+                    _ => return Err(FenError::InvalidCastling(fields[2].to_string())),
+                };
+                if castling_squares.contains(&(king_square, rook_square)) {
+                    return Err(FenError::InvalidCastling(fields[2].to_string()));
+                }
+                if board.pieces()[king_square] != PRESENT | color | KING || board.pieces()[rook_square] != PRESENT | color | ROOK {
+                    return Err(FenError::UnavailableCastling(right));
+                }
+                castling_squares.push((king_square, rook_square));
+            }
+        }
+        for square in 0..64 {
+            let piece = board.pieces()[square];
+            let can_castle = match get_type(piece) {
+                KING => castling_squares.iter().any(|(king_square, _)| *king_square == square),
+                ROOK => castling_squares.iter().any(|(_, rook_square)| *rook_square == square),
+                _ => true,
+            };
+            if get_presence(piece) == PRESENT && !can_castle {
+                board.set_square(square, piece | HAS_MOVED);
+            }
+        }
+
+        if fields[3] != "-" {
+            let invalid_en_passant = || FenError::InvalidEnPassant(fields[3].to_string());
+            let square = parse_square(fields[3]).ok_or_else(invalid_en_passant)?;
+            let (expected_rank, pawn_square, pawn_color) = match side_to_move {
+                Color::White => (5, square.wrapping_sub(8), BLACK),
+                Color::Black => (2, square + 8, WHITE),
+            };
+            if square / 8 != expected_rank || board.pieces()[pawn_square] != PRESENT | pawn_color | PAWN || get_presence(board.pieces()[square]) == PRESENT {
+                return Err(invalid_en_passant());
+            }
+            let file = pawn_square % 8;
+            let has_capturer = [file.wrapping_sub(1), file + 1].iter().any(|capturer_file| {
+                *capturer_file < 8 && board.pieces()[pawn_square - file + capturer_file] == PRESENT | negate_color(pawn_color) | PAWN
+            });
+            if has_capturer {
+                board.set_square(pawn_square, PRESENT | pawn_color | PAWN | HAS_MOVED_TWO_SQUARES);
+            }
+        }
+
+        let (halfmove_clock, fullmove_number) = if fields.len() == 6 {
+            let parse_clock = |clock: &str| clock.parse::<usize>().map_err(|_| FenError::InvalidClock(clock.to_string()));
+            let fullmove_number = parse_clock(fields[5])?;
+            if fullmove_number == 0 {
+                return Err(FenError::InvalidClock(fields[5].to_string()));
+            }
+            (parse_clock(fields[4])?, fullmove_number)
+        } else {
+            (0, 1)
+        };
+
+        let relative_board = match side_to_move {
+            Color::White => board,
+            Color::Black => board.inverted(),
+        };
+        // The side to move could take the king.
+        if relative_board.inverted().in_check() {
+            return Err(FenError::OpponentInCheck(side_to_move.opposite()));
+        }
+        let mut position = Position::new(relative_board, side_to_move);
+        position.halfmove_clock = halfmove_clock;
+        position.fullmove_number = fullmove_number;
+        Ok(position)
+    }
+
+    pub fn to_fen(&self) -> String {
+        let board = self.absolute_board();
+        let mut ranks: Vec<String> = Vec::with_capacity(8);
+        for rank in (0..8).rev() {
+            let mut rank_description = String::new();
+            let mut empty_squares = 0;
+            for file in 0..8 {
+                let piece = board.get(rank, file);
+                if get_presence(piece) == EMPTY {
+                    empty_squares += 1;
+                    continue;
+                }
+                if empty_squares > 0 {
+                    rank_description += &empty_squares.to_string();
+                    empty_squares = 0;
+                }
+                rank_description.push(piece_char(piece));
+            }
+            if empty_squares > 0 {
+                rank_description += &empty_squares.to_string();
+            }
+            ranks.push(rank_description);
+        }
+
+        let side_to_move = match self.side_to_move {
+            Color::White => "w",
+            Color::Black => "b",
+        };
+        let mut castling = String::new();
+        for (has_right, right) in [
+            (self.castling_rights.white_king_side, 'K'),
+            (self.castling_rights.white_queen_side, 'Q'),
+            (self.castling_rights.black_king_side, 'k'),
+            (self.castling_rights.black_queen_side, 'q'),
+        ] {
+            if has_right {
+                castling.push(right);
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+        let en_passant = match self.en_passant_square {
+            Some(square) => square_name(square),
+            None => String::from("-"),
+        };
+        format!("{} {} {} {} {} {}", ranks.join("/"), side_to_move, castling, en_passant, self.halfmove_clock, self.fullmove_number)
+    }
+}
+
+impl Board {
+    /// The board of a FEN position, seen from the side to move.
+    pub fn from_fen(fen: &str) -> Result<Board, FenError> {
+        Position::from_fen(fen).map(|position| position.board)
+    }
+
+    /// Boards are always seen from the side to move, so the FEN has White to move and the clocks at their defaults.
+    pub fn to_fen(&self) -> String {
+        Position::new(*self, Color::White).to_fen()
+    }
+}
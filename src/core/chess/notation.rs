@@ -0,0 +1,377 @@
+use std::fmt::Display;
+
+use crate::core::chess::{chess_move::Move, piece::*, position::{Color, Position}, square::{file_name, parse_file, parse_rank, parse_square, square_name}};
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum NotationError {
+    InvalidSyntax(String),
+    IllegalMove(String),
+    AmbiguousMove { notation: String, candidates: Vec<String> },
+    MissingPromotion(String),
+}
+
+impl Display for NotationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotationError::InvalidSyntax(notation) => write!(f, "'{}' is not a move, expected SAN like Nf3 or O-O, or coordinates like e2e4", notation),
+            NotationError::IllegalMove(notation) => write!(f, "{} is not a legal move in this position", notation),
+            NotationError::AmbiguousMove { notation, candidates } => write!(f, "{} is ambiguous, it could be {}", notation, candidates.join(" or ")),
+            NotationError::MissingPromotion(notation) => write!(f, "{} reaches the last rank, add the piece to promote to, e.g. {}=Q", notation, notation),
+        }
+    }
+}
+
+impl std::error::Error for NotationError {}
+
+fn parse_piece_letter(c: char) -> Option<u8> {
+    match c {
+        'N' => Some(KNIGHT),
+        'B' => Some(BISHOP),
+        'R' => Some(ROOK),
+        'Q' => Some(QUEEN),
+        'K' => Some(KING),
+        _ => None,
+    }
+}
+
+fn parse_promotion(c: char) -> Option<u8> {
+    match c.to_ascii_uppercase() {
+        'K' => None,
+        c => parse_piece_letter(c),
+    }
+}
+
+impl Position {
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.board.legal_moves()
+    }
+
+    /// Long algebraic notation as used by UCI, e.g. `e2e4`, `e1g1` for castling or `e7e8q`.
+    pub fn to_uci(&self, mv: Move) -> String {
+        let promotion = if mv.is_promotion() { get_type_string(mv.promotion).to_lowercase() } else { String::new() };
+        format!("{}{}{}", square_name(self.to_absolute_square(mv.from as usize)), square_name(self.to_absolute_square(mv.to as usize)), promotion)
+    }
+
+    /// Standard Algebraic Notation, e.g. `Nf3`, `exd5`, `O-O` or `e8=Q+`. `mv` has to be legal in this position.
+    pub fn to_san(&self, mv: Move) -> String {
+        let piece_type = get_type(self.board.pieces()[mv.from as usize]);
+        let from = self.to_absolute_square(mv.from as usize);
+        let to = self.to_absolute_square(mv.to as usize);
+        let mut san = String::new();
+        if mv.is_castling() {
+            san += if to > from { "O-O" } else { "O-O-O" };
+        } else if piece_type == PAWN {
+            if mv.is_capture() {
+                san.push(file_name(from % 8));
+                san.push('x');
+            }
+            san += &square_name(to);
+            if mv.is_promotion() {
+                san.push('=');
+                san += &get_type_string(mv.promotion);
+            }
+        } else {
+            san += &get_type_string(piece_type);
+            // Name the file of the moving piece if that tells it apart from the others of its kind that can reach the
+            // same square, then the rank, and both if neither is enough on its own.
+            let others: Vec<usize> = self.legal_moves().iter()
+                .filter(|other| other.to == mv.to && other.from != mv.from && get_type(self.board.pieces()[other.from as usize]) == piece_type)
+                .map(|other| self.to_absolute_square(other.from as usize))
+                .collect();
+            if !others.is_empty() {
+                if others.iter().all(|other| other % 8 != from % 8) {
+                    san.push(file_name(from % 8));
+                } else if others.iter().all(|other| other / 8 != from / 8) {
+                    san += &(from / 8 + 1).to_string();
+                } else {
+                    san += &square_name(from);
+                }
+            }
+            if mv.is_capture() {
+                san.push('x');
+            }
+            san += &square_name(to);
+        }
+        let next_board = self.board.next_board(mv);
+        if next_board.in_check() {
+            san.push(if next_board.legal_moves().is_empty() { '#' } else { '+' });
+        }
+        san
+    }
+
+    /// A line of moves starting in this position, each in long algebraic notation.
+    pub fn line_to_uci(&self, moves: &[Move]) -> Vec<String> {
+        let mut position = self.clone();
+        moves.iter().map(|mv| {
+            let notation = position.to_uci(*mv);
+            position.play(*mv);
+            notation
+        }).collect()
+    }
+
+    /// A line of moves starting in this position, each in SAN.
+    pub fn line_to_san(&self, moves: &[Move]) -> Vec<String> {
+        let mut position = self.clone();
+        moves.iter().map(|mv| {
+            let notation = position.to_san(*mv);
+            position.play(*mv);
+            notation
+        }).collect()
+    }
+
+    /// A line of moves starting in this position in SAN with move numbers, as in `12. Nf3 Nc6 13. Bb5` or
+    /// `12... Nc6 13. Bb5` when Black is to move.
+    pub fn line_to_numbered_san(&self, moves: &[Move]) -> String {
+        let mut position = self.clone();
+        let mut tokens = vec![];
+        for (ply, mv) in moves.iter().enumerate() {
+            match position.side_to_move {
+                Color::White => tokens.push(format!("{}.", position.fullmove_number)),
+                Color::Black if ply == 0 => tokens.push(format!("{}...", position.fullmove_number)),
+                Color::Black => {},
+            }
+            tokens.push(position.to_san(*mv));
+            position.play(*mv);
+        }
+        tokens.join(" ")
+    }
+
+    /// Parses a move in SAN or in long algebraic notation. The older `e2-e4` form of the prompt is accepted as well.
+    pub fn parse_move(&self, notation: &str) -> Result<Move, NotationError> {
+        let notation = notation.trim();
+        let coordinates = notation.replacen('-', "", 1);
+        if (coordinates.len() == 4 || coordinates.len() == 5) && coordinates.is_ascii() && parse_square(&coordinates[0..2]).is_some() && parse_square(&coordinates[2..4]).is_some() {
+            self.parse_uci(&coordinates)
+        } else {
+            self.parse_san(notation)
+        }
+    }
+
+    pub fn parse_uci(&self, notation: &str) -> Result<Move, NotationError> {
+        let invalid_syntax = || NotationError::InvalidSyntax(notation.to_string());
+        if !notation.is_ascii() || (notation.len() != 4 && notation.len() != 5) {
+            return Err(invalid_syntax());
+        }
+        let from = parse_square(&notation[0..2]).ok_or_else(invalid_syntax)?;
+        let to = parse_square(&notation[2..4]).ok_or_else(invalid_syntax)?;
+        let promotion = match notation.chars().nth(4) {
+            Some(c) => parse_promotion(c).ok_or_else(invalid_syntax)?,
+            None => 0,
+        };
+        let (from, to) = (self.to_relative_square(from), self.to_relative_square(to));
+        let candidates: Vec<Move> = self.legal_moves().into_iter().filter(|mv| mv.from as usize == from && mv.to as usize == to).collect();
+        if candidates.is_empty() {
+            return Err(NotationError::IllegalMove(notation.to_string()));
+        }
+        if promotion == 0 && candidates.iter().any(|mv| mv.is_promotion()) {
+            return Err(NotationError::MissingPromotion(notation.to_string()));
+        }
+        candidates.into_iter().find(|mv| mv.promotion == promotion).ok_or_else(|| NotationError::IllegalMove(notation.to_string()))
+    }
+
+    /// Parses Standard Algebraic Notation. Check, mate and annotation suffixes are ignored, and so is a missing or
+    /// superfluous capture sign, as long as the move it describes is unambiguous.
+    pub fn parse_san(&self, notation: &str) -> Result<Move, NotationError> {
+        let invalid_syntax = || NotationError::InvalidSyntax(notation.to_string());
+        let san = notation.trim_end_matches(['+', '#', '!', '?']);
+        if !san.is_ascii() || san.is_empty() {
+            return Err(invalid_syntax());
+        }
+        let legal_moves = self.legal_moves();
+
+        let castling = match san {
+            "O-O" | "0-0" => Some(true),
+            "O-O-O" | "0-0-0" => Some(false),
+            _ => None,
+        };
+        if let Some(king_side) = castling {
+            return legal_moves.into_iter().find(|mv| {
+                mv.is_castling() && (self.to_absolute_square(mv.to as usize) > self.to_absolute_square(mv.from as usize)) == king_side
+            }).ok_or_else(|| NotationError::IllegalMove(notation.to_string()));
+        }
+
+        let mut chars: Vec<char> = san.chars().collect();
+        let piece_type = match parse_piece_letter(chars[0]) {
+            Some(piece_type) => {
+                chars.remove(0);
+                piece_type
+            },
+            None => PAWN,
+        };
+        let mut promotion = 0;
+        if piece_type == PAWN && chars.len() > 2
+            && let Some(piece_type) = parse_promotion(chars[chars.len() - 1]).filter(|_| chars[chars.len() - 1].is_ascii_uppercase()) {
+            promotion = piece_type;
+            chars.pop();
+            if chars.last() == Some(&'=') {
+                chars.pop();
+            }
+        }
+        if chars.len() < 2 {
+            return Err(invalid_syntax());
+        }
+        let destination: String = chars[chars.len() - 2..].iter().collect();
+        let to = parse_square(&destination).ok_or_else(invalid_syntax)?;
+        let mut from_file = None;
+        let mut from_rank = None;
+        for c in &chars[..chars.len() - 2] {
+            match *c {
+                'x' => {},
+                c if parse_file(c).is_some() && from_file.is_none() => from_file = parse_file(c),
+                c if parse_rank(c).is_some() && from_rank.is_none() => from_rank = parse_rank(c),
+                _ => return Err(invalid_syntax()),
+            }
+        }
+
+        let candidates: Vec<Move> = legal_moves.into_iter().filter(|mv| {
+            let from = self.to_absolute_square(mv.from as usize);
+            !mv.is_castling()
+                && self.to_absolute_square(mv.to as usize) == to
+                && get_type(self.board.pieces()[mv.from as usize]) == piece_type
+                && from_file.is_none_or(|file| from % 8 == file)
+                && from_rank.is_none_or(|rank| from / 8 == rank)
+        }).collect();
+        if candidates.is_empty() {
+            return Err(NotationError::IllegalMove(notation.to_string()));
+        }
+        if promotion == 0 && candidates.iter().any(|mv| mv.is_promotion()) {
+            return Err(NotationError::MissingPromotion(notation.to_string()));
+        }
+        let candidates: Vec<Move> = candidates.into_iter().filter(|mv| mv.promotion == promotion).collect();
+        match candidates.len() {
+            0 => Err(NotationError::IllegalMove(notation.to_string())),
+            1 => Ok(candidates[0]),
+            _ => Err(NotationError::AmbiguousMove {
+                notation: notation.to_string(),
+                candidates: candidates.iter().map(|mv| self.to_san(*mv)).collect(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(fen: &str) -> Position {
+        Position::from_fen(fen).unwrap()
+    }
+
+    fn played(moves: &[&str]) -> Position {
+        let mut position = Position::initial();
+        for notation in moves {
+            let mv = position.parse_move(notation).unwrap();
+            position.play(mv);
+        }
+        position
+    }
+
+    /// The SAN of the move given in coordinates, checked to read back as the same move.
+    fn san(position: &Position, uci: &str) -> String {
+        let mv = position.parse_uci(uci).unwrap();
+        let san = position.to_san(mv);
+        assert_eq!(position.parse_san(&san), Ok(mv), "{} does not read back", san);
+        san
+    }
+
+    fn uci(position: &Position, san: &str) -> Result<String, NotationError> {
+        position.parse_san(san).map(|mv| position.to_uci(mv))
+    }
+
+    #[test]
+    fn moves_are_told_apart_by_file() {
+        let position = position("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1");
+        assert_eq!(san(&position, "b1d2"), "Nbd2");
+        assert_eq!(san(&position, "f1d2"), "Nfd2");
+        assert_eq!(san(&position, "f1g3"), "Ng3");
+        assert_eq!(uci(&position, "Nfd2"), Ok(String::from("f1d2")));
+    }
+
+    #[test]
+    fn moves_are_told_apart_by_rank() {
+        let position = position("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1");
+        assert_eq!(san(&position, "a1a3"), "R1a3");
+        assert_eq!(san(&position, "a5a3"), "R5a3");
+        assert_eq!(uci(&position, "R5a3"), Ok(String::from("a5a3")));
+    }
+
+    #[test]
+    fn moves_are_told_apart_by_square() {
+        // Another queen shares the file of a1 and a third its rank.
+        let position = position("4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1");
+        assert_eq!(san(&position, "a1b2"), "Qa1b2");
+        assert_eq!(san(&position, "a3b2"), "Q3b2");
+        assert_eq!(san(&position, "c1b2"), "Qcb2");
+    }
+
+    #[test]
+    fn ambiguous_moves_are_rejected() {
+        let position = position("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1");
+        match uci(&position, "Nd2") {
+            Err(NotationError::AmbiguousMove { notation, mut candidates }) => {
+                candidates.sort();
+                assert_eq!(notation, "Nd2");
+                assert_eq!(candidates, ["Nbd2", "Nfd2"]);
+            },
+            result => panic!("Nd2 read as {:?}", result),
+        }
+    }
+
+    #[test]
+    fn castling_is_written_with_letter_o() {
+        let white = position("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert_eq!(san(&white, "e1g1"), "O-O");
+        assert_eq!(san(&white, "e1c1"), "O-O-O");
+        assert_eq!(uci(&white, "0-0"), Ok(String::from("e1g1")));
+        let black = position("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1");
+        assert_eq!(san(&black, "e8g8"), "O-O");
+        assert_eq!(san(&black, "e8c8"), "O-O-O");
+        assert_eq!(uci(&black, "O-O-O"), Ok(String::from("e8c8")));
+        let without_rights = position("r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1");
+        assert_eq!(uci(&without_rights, "O-O"), Err(NotationError::IllegalMove(String::from("O-O"))));
+    }
+
+    #[test]
+    fn en_passant_is_a_pawn_capture() {
+        let position = position("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+        assert_eq!(san(&position, "e5f6"), "exf6");
+        assert_eq!(uci(&position, "ef6"), Ok(String::from("e5f6")));
+        assert_eq!(uci(&position, "exd6"), Err(NotationError::IllegalMove(String::from("exd6"))));
+    }
+
+    #[test]
+    fn promotions_may_leave_out_the_equals_sign() {
+        let position = position("8/P7/8/8/8/8/8/k6K w - - 0 1");
+        assert_eq!(san(&position, "a7a8q"), "a8=Q+");
+        assert_eq!(san(&position, "a7a8n"), "a8=N");
+        assert_eq!(uci(&position, "a8=Q"), Ok(String::from("a7a8q")));
+        assert_eq!(uci(&position, "a8R"), Ok(String::from("a7a8r")));
+        assert_eq!(position.parse_move("a7a8b").map(|mv| position.to_san(mv)), Ok(String::from("a8=B")));
+    }
+
+    #[test]
+    fn promotions_have_to_name_the_piece() {
+        let position = position("8/P7/8/8/8/8/8/k6K w - - 0 1");
+        assert_eq!(uci(&position, "a8"), Err(NotationError::MissingPromotion(String::from("a8"))));
+        assert_eq!(position.parse_uci("a7a8"), Err(NotationError::MissingPromotion(String::from("a7a8"))));
+        assert_eq!(uci(&position, "a8=K"), Err(NotationError::InvalidSyntax(String::from("a8=K"))));
+    }
+
+    #[test]
+    fn checks_and_mates_are_marked() {
+        assert_eq!(san(&played(&["e4", "e5", "Bc4", "Nc6"]), "c4f7"), "Bxf7+");
+        let position = played(&["f3", "e5", "g4"]);
+        assert_eq!(san(&position, "d8h4"), "Qh4#");
+        for notation in ["Qh4", "Qh4+", "Qh4#", "Qh4!?"] {
+            assert_eq!(uci(&position, notation), Ok(String::from("d8h4")), "{}", notation);
+        }
+    }
+
+    #[test]
+    fn malformed_moves_are_rejected() {
+        let position = Position::initial();
+        assert_eq!(uci(&position, "Nf9"), Err(NotationError::InvalidSyntax(String::from("Nf9"))));
+        assert_eq!(uci(&position, "N"), Err(NotationError::InvalidSyntax(String::from("N"))));
+        assert_eq!(uci(&position, "Ke2"), Err(NotationError::IllegalMove(String::from("Ke2"))));
+    }
+}
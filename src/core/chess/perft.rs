@@ -0,0 +1,42 @@
+use crate::core::chess::{board::Board, chess_move::Move};
+
+impl Board {
+    /// The number of positions reached by every sequence of `depth` legal moves from the board. Comparing it with the
+    /// known counts of reference positions checks the move generator.
+    pub fn perft(&self, depth: usize) -> usize {
+        let mut board = *self;
+        perft(&mut board, depth)
+    }
+
+    /// `perft` for each of the board's legal moves, counting the positions `depth` - 1 plies after it.
+    pub fn divide(&self, depth: usize) -> Vec<(Move, usize)> {
+        if depth == 0 {
+            return vec![];
+        }
+        let mut board = *self;
+        board.legal_moves().into_iter().map(|mv| {
+            let undo = board.make_move(mv);
+            let nodes = perft(&mut board, depth - 1);
+            board.unmake_move(mv, undo);
+            (mv, nodes)
+        }).collect()
+    }
+}
+
+fn perft(board: &mut Board, depth: usize) -> usize {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = board.legal_moves();
+    // The positions one ply away are only counted, so there is no need to play the moves leading to them.
+    if depth == 1 {
+        return moves.len();
+    }
+    let mut nodes = 0;
+    for mv in moves {
+        let undo = board.make_move(mv);
+        nodes += perft(board, depth - 1);
+        board.unmake_move(mv, undo);
+    }
+    nodes
+}
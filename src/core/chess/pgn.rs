@@ -0,0 +1,429 @@
+use std::fmt::Display;
+
+use crate::core::chess::{board_state::{Evaluation, PositionResult}, fen::{FenError, INITIAL_FEN}, notation::NotationError, position::{Color, Position}};
+
+// Export format lines are kept below this many characters.
+const LINE_LENGTH: usize = 80;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum PgnError {
+    UnterminatedTag,
+    InvalidTag(String),
+    UnterminatedComment,
+    InvalidNag(String),
+    UnbalancedVariation,
+    InvalidFen(FenError),
+    IllegalMove { move_number: String, error: NotationError },
+    NoGame,
+}
+
+impl Display for PgnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PgnError::UnterminatedTag => write!(f, "a tag pair is missing its closing ']'"),
+            PgnError::InvalidTag(tag) => write!(f, "'[{}]' is not a tag pair, expected [Name \"Value\"]", tag),
+            PgnError::UnterminatedComment => write!(f, "a comment is missing its closing '}}'"),
+            PgnError::InvalidNag(nag) => write!(f, "'{}' is not a NAG, expected '$' followed by a number from 0 to 255", nag),
+            PgnError::UnbalancedVariation => write!(f, "the parentheses around the variations do not match"),
+            PgnError::InvalidFen(error) => write!(f, "invalid FEN tag: {}", error),
+            PgnError::IllegalMove { move_number, error } => write!(f, "move {}: {}", move_number, error),
+            PgnError::NoGame => write!(f, "there is no game in the PGN"),
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+/// A move of the movetext with its annotations. `variations` are alternatives to this move, each played from the
+/// position before it.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct PgnMove {
+    pub san: String,
+    pub nags: Vec<u8>,
+    pub comments_before: Vec<String>,
+    pub comments: Vec<String>,
+    pub variations: Vec<Vec<PgnMove>>,
+}
+
+impl PgnMove {
+    pub fn new(san: String) -> Self {
+        PgnMove { san, ..Default::default() }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<PgnMove>,
+    pub result: String,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    Nag(u8),
+    Move(String),
+    Result(String),
+    OpenVariation,
+    CloseVariation,
+}
+
+fn suffix_nag(suffix: &str) -> Option<u8> {
+    match suffix {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None,
+    }
+}
+
+fn is_result(token: &str) -> bool {
+    matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*")
+}
+
+/// The index of the ']' closing the tag pair opened at `start`, skipping the ones inside its quoted value.
+fn tag_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut in_string = false;
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if in_string => i += 1,
+            '"' => in_string = !in_string,
+            ']' if !in_string => return Some(i),
+            _ => {},
+        }
+        i += 1;
+    }
+    None
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, PgnError> {
+    let mut tokens = vec![];
+    let chars: Vec<char> = text.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        // Lines starting with '%' are escaped and ignored, as are the rest of the lines after ';'.
+        if c == ';' || (c == '%' && (i == 0 || chars[i - 1] == '\n')) {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        match c {
+            '[' => {
+                let end = tag_end(&chars, i).ok_or(PgnError::UnterminatedTag)?;
+                let tag: String = chars[i + 1..end].iter().collect();
+                let (name, value) = tag.trim().split_once(char::is_whitespace).ok_or_else(|| PgnError::InvalidTag(tag.clone()))?;
+                let value = value.trim();
+                if value.len() < 2 || !value.starts_with('"') || !value.ends_with('"') {
+                    return Err(PgnError::InvalidTag(tag.clone()));
+                }
+                let value = value[1..value.len() - 1].replace("\\\"", "\"").replace("\\\\", "\\");
+                tokens.push(Token::Tag(name.to_string(), value));
+                i = end + 1;
+            },
+            '{' => {
+                let end = chars[i..].iter().position(|c| *c == '}').ok_or(PgnError::UnterminatedComment)? + i;
+                let comment: String = chars[i + 1..end].iter().collect();
+                tokens.push(Token::Comment(comment.split_whitespace().collect::<Vec<&str>>().join(" ")));
+                i = end + 1;
+            },
+            '(' => {
+                tokens.push(Token::OpenVariation);
+                i += 1;
+            },
+            ')' => {
+                tokens.push(Token::CloseVariation);
+                i += 1;
+            },
+            _ => {
+                let start = i;
+                while i < chars.len() && !chars[i].is_whitespace() && !"[]{}();".contains(chars[i]) {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                if is_result(&word) {
+                    tokens.push(Token::Result(word));
+                } else if let Some(nag) = word.strip_prefix('$') {
+                    tokens.push(Token::Nag(nag.parse().map_err(|_| PgnError::InvalidNag(word.clone()))?));
+                } else if let Some(nag) = suffix_nag(&word) {
+                    tokens.push(Token::Nag(nag));
+                } else {
+                    // Move numbers ("12." or "12...") may be written right against the move that follows them.
+                    let digits = word.chars().take_while(|c| c.is_ascii_digit()).count();
+                    let word = if digits > 0 && word[digits..].starts_with('.') { word[digits..].trim_start_matches('.') } else { word.as_str() };
+                    if word.is_empty() {
+                        continue;
+                    }
+                    let san = word.trim_end_matches(['!', '?']);
+                    tokens.push(Token::Move(san.to_string()));
+                    if let Some(nag) = suffix_nag(&word[san.len()..]) {
+                        tokens.push(Token::Nag(nag));
+                    }
+                }
+            },
+        }
+    }
+    Ok(tokens)
+}
+
+impl Default for PgnGame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PgnGame {
+    pub fn new() -> Self {
+        PgnGame { tags: vec![], moves: vec![], result: String::from("*") }
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(tag_name, _)| tag_name == name).map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag_name, _)| tag_name == name) {
+            Some(tag) => tag.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// Parses the first game of a PGN file.
+    pub fn parse(text: &str) -> Result<PgnGame, PgnError> {
+        PgnGame::parse_all(text)?.into_iter().next().ok_or(PgnError::NoGame)
+    }
+
+    /// Parses every game of a PGN file. Games end with their result, or where the tags of the next game start.
+    pub fn parse_all(text: &str) -> Result<Vec<PgnGame>, PgnError> {
+        let mut games = vec![];
+        let mut game = PgnGame::new();
+        let mut has_content = false;
+        // The lines being read, innermost variation last, and the comments waiting for the move they precede.
+        let mut lines: Vec<Vec<PgnMove>> = vec![vec![]];
+        let mut pending_comments: Vec<String> = vec![];
+        for token in tokenize(text)? {
+            match token {
+                Token::Tag(name, value) => {
+                    if lines.len() == 1 && !lines[0].is_empty() {
+                        game.moves = lines.pop().unwrap();
+                        games.push(game);
+                        game = PgnGame::new();
+                        lines = vec![vec![]];
+                    }
+                    game.tags.push((name, value));
+                },
+                Token::Comment(comment) => match lines.last_mut().unwrap().last_mut() {
+                    Some(last_move) if pending_comments.is_empty() => last_move.comments.push(comment),
+                    _ => pending_comments.push(comment),
+                },
+                Token::Nag(nag) => {
+                    if let Some(last_move) = lines.last_mut().unwrap().last_mut() {
+                        last_move.nags.push(nag);
+                    }
+                },
+                Token::Move(san) => {
+                    let mut pgn_move = PgnMove::new(san);
+                    pgn_move.comments_before = std::mem::take(&mut pending_comments);
+                    lines.last_mut().unwrap().push(pgn_move);
+                },
+                Token::OpenVariation => {
+                    if lines.last().unwrap().is_empty() {
+                        return Err(PgnError::UnbalancedVariation);
+                    }
+                    lines.push(vec![]);
+                },
+                Token::CloseVariation => {
+                    if lines.len() < 2 {
+                        return Err(PgnError::UnbalancedVariation);
+                    }
+                    let variation = lines.pop().unwrap();
+                    lines.last_mut().unwrap().last_mut().unwrap().variations.push(variation);
+                },
+                Token::Result(result) => {
+                    if lines.len() != 1 {
+                        return Err(PgnError::UnbalancedVariation);
+                    }
+                    game.moves = lines.pop().unwrap();
+                    game.result = result;
+                    games.push(game);
+                    game = PgnGame::new();
+                    lines = vec![vec![]];
+                    pending_comments.clear();
+                    has_content = false;
+                    continue;
+                },
+            }
+            has_content = true;
+        }
+        if lines.len() != 1 {
+            return Err(PgnError::UnbalancedVariation);
+        }
+        if has_content {
+            game.moves = lines.pop().unwrap();
+            games.push(game);
+        }
+        Ok(games)
+    }
+
+    /// The position the game starts from, given by the FEN tag when there is one.
+    pub fn starting_position(&self) -> Result<Position, PgnError> {
+        Position::from_fen(self.tag("FEN").unwrap_or(INITIAL_FEN)).map_err(PgnError::InvalidFen)
+    }
+
+    /// Plays the main line from the starting position. Every move, including the ones in variations, has to be legal.
+    pub fn replay(&self) -> Result<Position, PgnError> {
+        let mut position = self.starting_position()?;
+        replay_line(&mut position, &self.moves)?;
+        Ok(position)
+    }
+
+    /// The game played to reach `position`, with the Seven Tag Roster left unknown and the result filled in.
+    pub fn from_position(position: &Position) -> PgnGame {
+        let mut starting_position = position.clone();
+        while starting_position.undo() {}
+        let mut game = PgnGame::new();
+        for name in ["Event", "Site", "Date", "Round", "White", "Black"] {
+            game.set_tag(name, "?");
+        }
+        let result = game_result(position);
+        game.set_tag("Result", result);
+        game.result = result.to_string();
+        let fen = starting_position.to_fen();
+        if fen != INITIAL_FEN {
+            game.set_tag("SetUp", "1");
+            game.set_tag("FEN", &fen);
+        }
+        for entry in position.history.iter() {
+            game.moves.push(PgnMove::new(starting_position.to_san(entry.mv)));
+            starting_position.play(entry.mv);
+        }
+        game
+    }
+}
+
+fn replay_line(position: &mut Position, line: &[PgnMove]) -> Result<(), PgnError> {
+    for pgn_move in line {
+        for variation in pgn_move.variations.iter() {
+            replay_line(&mut position.clone(), variation)?;
+        }
+        let mv = position.parse_san(&pgn_move.san).map_err(|error| PgnError::IllegalMove {
+            move_number: format!("{}{}", position.fullmove_number, if position.side_to_move == Color::White { "." } else { "..." }),
+            error,
+        })?;
+        position.play(mv);
+    }
+    Ok(())
+}
+
+/// The result tag for a game that has reached `position`.
+pub fn game_result(position: &Position) -> &'static str {
+    if position.legal_moves().is_empty() {
+        match (position.board.in_check(), position.side_to_move) {
+            (true, Color::White) => "0-1",
+            (true, Color::Black) => "1-0",
+            (false, _) => "1/2-1/2",
+        }
+    } else if position.draw_reason().is_some() || position.board.is_insufficient_material() {
+        "1/2-1/2"
+    } else {
+        "*"
+    }
+}
+
+/// An `[%eval]` comment for an evaluation seen from `color`. Scores are turned around to White's point of view, and
+/// mates are counted in moves rather than plies.
+pub fn evaluation_comment(evaluation: &Evaluation, color: Color) -> String {
+    let sign = if color == Color::White { 1 } else { -1 };
+    let value = match evaluation.result {
+        PositionResult::Win => format!("#{}", sign * (evaluation.score + 1) / 2),
+        PositionResult::Loss => format!("#{}", -sign * (evaluation.score + 1) / 2),
+        PositionResult::Draw => String::from("0.00"),
+        PositionResult::Scored => format!("{:.2}", (sign * evaluation.score) as f64),
+    };
+    format!("[%eval {}]", value)
+}
+
+fn escape_comment(comment: &str) -> String {
+    comment.replace('}', ")")
+}
+
+fn write_line(tokens: &mut Vec<String>, line: &[PgnMove], mut fullmove_number: usize, mut black_to_move: bool) {
+    let mut needs_number = true;
+    for pgn_move in line {
+        for comment in pgn_move.comments_before.iter() {
+            tokens.push(format!("{{{}}}", escape_comment(comment)));
+            needs_number = true;
+        }
+        // Move numbers stay on the same line as their move.
+        if !black_to_move {
+            tokens.push(format!("{}. {}", fullmove_number, pgn_move.san));
+        } else if needs_number {
+            tokens.push(format!("{}... {}", fullmove_number, pgn_move.san));
+        } else {
+            tokens.push(pgn_move.san.clone());
+        }
+        needs_number = false;
+        for nag in pgn_move.nags.iter() {
+            tokens.push(format!("${}", nag));
+        }
+        for comment in pgn_move.comments.iter() {
+            tokens.push(format!("{{{}}}", escape_comment(comment)));
+            needs_number = true;
+        }
+        for variation in pgn_move.variations.iter() {
+            let mut variation_tokens = vec![];
+            write_line(&mut variation_tokens, variation, fullmove_number, black_to_move);
+            if let Some(first) = variation_tokens.first_mut() {
+                first.insert(0, '(');
+            }
+            if let Some(last) = variation_tokens.last_mut() {
+                last.push(')');
+            }
+            tokens.append(&mut variation_tokens);
+            needs_number = true;
+        }
+        if black_to_move {
+            fullmove_number += 1;
+        }
+        black_to_move = !black_to_move;
+    }
+}
+
+/// Export format: the tags, an empty line and the movetext wrapped into lines.
+impl Display for PgnGame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, value) in self.tags.iter() {
+            writeln!(f, "[{} \"{}\"]", name, value.replace('\\', "\\\\").replace('"', "\\\""))?;
+        }
+        if !self.tags.is_empty() {
+            writeln!(f)?;
+        }
+        let starting_position = self.starting_position().unwrap_or_else(|_| Position::initial());
+        let mut tokens = vec![];
+        write_line(&mut tokens, &self.moves, starting_position.fullmove_number, starting_position.side_to_move == Color::Black);
+        tokens.push(self.result.clone());
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 && line_length + 1 + token.len() > LINE_LENGTH {
+                writeln!(f)?;
+                line_length = 0;
+            } else if line_length > 0 {
+                write!(f, " ")?;
+                line_length += 1;
+            }
+            write!(f, "{}", token)?;
+            line_length += token.len();
+        }
+        writeln!(f)
+    }
+}
@@ -96,12 +96,12 @@ pub fn get_material_value(b: u8) -> i64 {
 
 pub fn get_max_movement(b: u8) -> usize {
 	match get_type(b) {
-		PAWN => 4,
+		PAWN => 12,
 		ROOK => 14,
 		KNIGHT => 8,
 		BISHOP => 13,
 		QUEEN => 27,
-		KING => 8,
+		KING => 10,
         _ => panic!("Invalid piece type"),
 	}
 }
\ No newline at end of file
@@ -0,0 +1,280 @@
+use std::fmt::Display;
+
+use crate::core::chess::{board::Board, chess_move::Move, initial_board::INITIAL_BOARD, piece::*};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Color {
+    White,
+    Black,
+}
+
+impl Color {
+    pub fn opposite(&self) -> Self {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Color::White => "White",
+            Color::Black => "Black",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct CastlingRights {
+    pub white_king_side: bool,
+    pub white_queen_side: bool,
+    pub black_king_side: bool,
+    pub black_queen_side: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DrawReason {
+    ThreefoldRepetition,
+    FivefoldRepetition,
+    FiftyMoveRule,
+    SeventyFiveMoveRule,
+}
+
+impl Display for DrawReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            DrawReason::ThreefoldRepetition => "Draw by threefold repetition",
+            DrawReason::FivefoldRepetition => "Draw by fivefold repetition",
+            DrawReason::FiftyMoveRule => "Draw by the fifty-move rule",
+            DrawReason::SeventyFiveMoveRule => "Draw by the seventy-five-move rule",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct HistoryEntry {
+    pub board: Board,
+    pub mv: Move,
+    pub halfmove_clock: usize,
+}
+
+/// A game position. `board` is always seen from the side to move, like every other board in the engine, while the
+/// remaining fields are in absolute terms: squares are numbered from a1 = 0 to h8 = 63 from White's point of view.
+/// Castling rights and the en passant square are derived from the flags carried by the pieces on `board`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Position {
+    pub board: Board,
+    pub side_to_move: Color,
+    pub castling_rights: CastlingRights,
+    pub en_passant_square: Option<usize>,
+    pub halfmove_clock: usize,
+    pub fullmove_number: usize,
+    pub history: Vec<HistoryEntry>,
+}
+
+impl Position {
+    pub fn new(board: Board, side_to_move: Color) -> Self {
+        let mut position = Position {
+            board,
+            side_to_move,
+            castling_rights: CastlingRights::default(),
+            en_passant_square: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            history: vec![],
+        };
+        position.refresh();
+        position
+    }
+
+    pub fn initial() -> Self {
+        Position::new(INITIAL_BOARD, Color::White)
+    }
+
+    /// The board seen from White's side.
+    pub fn absolute_board(&self) -> Board {
+        match self.side_to_move {
+            Color::White => self.board,
+            Color::Black => self.board.inverted(),
+        }
+    }
+
+    /// Converts an absolute square into the square it occupies on `board`.
+    pub fn to_relative_square(&self, square: usize) -> usize {
+        match self.side_to_move {
+            Color::White => square,
+            Color::Black => 63 - square,
+        }
+    }
+
+    pub fn to_absolute_square(&self, square: usize) -> usize {
+        self.to_relative_square(square)
+    }
+
+    /// The absolute squares of the pieces of either side that the other side wins material by taking.
+    pub fn hanging_pieces(&self) -> Vec<usize> {
+        let opponent_pieces = self.board.inverted().hanging_pieces().into_iter().map(|square| 63 - square);
+        let mut squares: Vec<usize> = self.board.hanging_pieces().into_iter()
+            .chain(opponent_pieces)
+            .map(|square| self.to_absolute_square(square))
+            .collect();
+        squares.sort();
+        squares
+    }
+
+    /// Plays `mv`, which has to be one of the moves generated for `board`.
+    pub fn play(&mut self, mv: Move) {
+        self.history.push(HistoryEntry { board: self.board, mv, halfmove_clock: self.halfmove_clock });
+        if mv.is_capture() || get_type(self.board.pieces()[mv.from as usize]) == PAWN {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if self.side_to_move == Color::Black {
+            self.fullmove_number += 1;
+        }
+        self.side_to_move = self.side_to_move.opposite();
+        self.board.make_move(mv);
+        self.refresh();
+    }
+
+    /// Takes back the last move. Returns false when there is nothing to take back.
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            None => false,
+            Some(entry) => {
+                self.side_to_move = self.side_to_move.opposite();
+                if self.side_to_move == Color::Black {
+                    self.fullmove_number -= 1;
+                }
+                self.board = entry.board;
+                self.halfmove_clock = entry.halfmove_clock;
+                self.refresh();
+                true
+            }
+        }
+    }
+
+    /// The line of at most `length` moves that `next_move` picks from the current position on. It ends at a position
+    /// `next_move` has no move for, or with the move back to a position of the line, from which it would only repeat.
+    pub fn follow_line(&self, length: usize, mut next_move: impl FnMut(&Board) -> Option<Move>) -> Vec<Move> {
+        let mut position = self.clone();
+        let mut visited = std::collections::HashSet::from([position.board]);
+        let mut moves = vec![];
+        while moves.len() < length {
+            let Some(mv) = next_move(&position.board) else {
+                break;
+            };
+            moves.push(mv);
+            position.play(mv);
+            if !visited.insert(position.board) {
+                break;
+            }
+        }
+        moves
+    }
+
+    /// How many times the current position has occurred, counting the current occurrence. Only the moves since the
+    /// last capture or pawn move can repeat, and only every other one has the same side to move.
+    pub fn repetition_count(&self) -> usize {
+        let mut count = 1;
+        let window = self.halfmove_clock.min(self.history.len());
+        for distance in (2..=window).step_by(2) {
+            if self.history[self.history.len() - distance].board.key() == self.board.key() {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    pub fn is_threefold_repetition(&self) -> bool {
+        self.repetition_count() >= 3
+    }
+
+    pub fn is_fivefold_repetition(&self) -> bool {
+        self.repetition_count() >= 5
+    }
+
+    pub fn is_fifty_move_rule(&self) -> bool {
+        self.halfmove_clock >= 100
+    }
+
+    pub fn is_seventy_five_move_rule(&self) -> bool {
+        self.halfmove_clock >= 150
+    }
+
+    /// The automatic draws take precedence over the ones that would have to be claimed.
+    pub fn draw_reason(&self) -> Option<DrawReason> {
+        if self.is_fivefold_repetition() {
+            Some(DrawReason::FivefoldRepetition)
+        } else if self.is_seventy_five_move_rule() {
+            Some(DrawReason::SeventyFiveMoveRule)
+        } else if self.is_threefold_repetition() {
+            Some(DrawReason::ThreefoldRepetition)
+        } else if self.is_fifty_move_rule() {
+            Some(DrawReason::FiftyMoveRule)
+        } else {
+            None
+        }
+    }
+
+    /// Whether `board`, reached after `ply` plies of the game (the length `history` would have), repeats a position
+    /// that has already been played. Positions ahead of the current one are assumed to be reached by the search, which
+    /// scores the first repetition as a draw.
+    pub fn repeats_history(&self, board: &Board, ply: usize) -> bool {
+        let current_ply = self.history.len();
+        if ply <= current_ply {
+            return false;
+        }
+        if (ply - current_ply).is_multiple_of(2) && self.board.key() == board.key() {
+            return true;
+        }
+        let window = self.halfmove_clock.min(current_ply);
+        for distance in 1..=window {
+            if (ply - current_ply + distance).is_multiple_of(2) && self.history[current_ply - distance].board.key() == board.key() {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Whether `board`, reached after `ply` plies of the game, completes fifty moves without a capture or pawn move.
+    /// A board with the same arrangement as the current one can only be reached through such moves.
+    pub fn exceeds_fifty_moves(&self, board: &Board, ply: usize) -> bool {
+        let current_ply = self.history.len();
+        if ply <= current_ply || board.get_board_arrangement() != self.board.get_board_arrangement() {
+            return false;
+        }
+        self.halfmove_clock + (ply - current_ply) >= 100
+    }
+
+    fn refresh(&mut self) {
+        let board = self.absolute_board();
+        let unmoved = |square: usize, color: u8, piece_type: u8| board.pieces()[square] == PRESENT | color | piece_type | HAS_NOT_MOVED;
+        let white_king = unmoved(4, WHITE, KING);
+        let black_king = unmoved(60, BLACK, KING);
+        self.castling_rights = CastlingRights {
+            white_king_side: white_king && unmoved(7, WHITE, ROOK),
+            white_queen_side: white_king && unmoved(0, WHITE, ROOK),
+            black_king_side: black_king && unmoved(63, BLACK, ROOK),
+            black_queen_side: black_king && unmoved(56, BLACK, ROOK),
+        };
+        self.en_passant_square = None;
+        for square in 24..40 {
+            let piece = board.pieces()[square];
+            if get_presence(piece) == PRESENT && get_type(piece) == PAWN && get_has_moved_two_squares(piece) {
+                self.en_passant_square = Some(if get_color(piece) == WHITE { square - 8 } else { square + 8 });
+            }
+        }
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}\n{} to move, move {}", self.absolute_board(), self.side_to_move, self.fullmove_number)
+    }
+}
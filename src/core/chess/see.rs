@@ -0,0 +1,73 @@
+use crate::core::chess::{bitboard::{OPPONENT, SIDE_TO_MOVE}, board::Board, chess_move::Move, piece::*};
+
+// The order pieces join an exchange in, the least valuable first.
+const EXCHANGE_ORDER: [u8; 6] = [PAWN, KNIGHT, BISHOP, ROOK, QUEEN, KING];
+// Taking the king would end the game, so it outweighs anything an exchange can win.
+const KING_VALUE: i32 = 100;
+
+fn exchange_value(piece_type: u8) -> i32 {
+    if piece_type == KING { KING_VALUE } else { get_material_value(piece_type) as i32 }
+}
+
+impl Board {
+    /// Static exchange evaluation: the material the side to move wins by playing `mv` and letting both sides take
+    /// back on its square with their least valuable piece for as long as that pays, counting the sliders that the
+    /// exchange uncovers behind the pieces that leave. Pins and checks elsewhere on the board are not looked at.
+    pub fn see(&self, mv: &Move) -> i32 {
+        let bitboards = self.bitboards();
+        let to = mv.to as usize;
+        let captured = self.pieces()[mv.captured_square()];
+        // What each capture of the exchange wins for the side making it, if the exchange stops right after it.
+        let mut gains = [0; 32];
+        gains[0] = if mv.is_capture() && get_presence(captured) == PRESENT { exchange_value(get_type(captured)) } else { 0 };
+        let mut on_square = get_type(self.pieces()[mv.from as usize]);
+        if mv.is_promotion() {
+            gains[0] += exchange_value(mv.promotion) - exchange_value(PAWN);
+            on_square = get_type(mv.promotion);
+        }
+        let mut occupied = bitboards.occupied() & !(1 << mv.from) & !(1 << mv.captured_square());
+        let mut side = OPPONENT;
+        let mut depth = 0;
+        loop {
+            let attackers = bitboards.attackers(to, occupied) & occupied & bitboards.sides[side];
+            let least_valuable = EXCHANGE_ORDER.iter().find_map(|piece_type| {
+                let pieces = bitboards.pieces(side, *piece_type) & attackers;
+                (pieces != 0).then(|| (*piece_type, pieces & pieces.wrapping_neg()))
+            });
+            let Some((piece_type, attacker)) = least_valuable else {
+                break;
+            };
+            depth += 1;
+            gains[depth] = exchange_value(on_square) - gains[depth - 1];
+            on_square = piece_type;
+            // A pawn taking on the last rank promotes.
+            let last_rank = if side == SIDE_TO_MOVE { to >= 56 } else { to < 8 };
+            if piece_type == PAWN && last_rank {
+                gains[depth] += exchange_value(QUEEN) - exchange_value(PAWN);
+                on_square = QUEEN;
+            }
+            occupied &= !attacker;
+            side = 1 - side;
+        }
+        // Going back up the exchange, each side stops as soon as going on would lose it more than stopping.
+        while depth > 0 {
+            gains[depth - 1] = -(-gains[depth - 1]).max(gains[depth]);
+            depth -= 1;
+        }
+        gains[0]
+    }
+
+    /// The squares of the side to move's pieces that the opponent wins material by taking, as `see` counts it. The
+    /// opponent is given the move as by a null move, so it cannot take the pawns the side to move could take en
+    /// passant.
+    pub fn hanging_pieces(&self) -> Vec<usize> {
+        let opponent = self.null_move();
+        let mut squares: Vec<usize> = opponent.legal_moves().iter()
+            .filter(|mv| mv.is_capture() && opponent.see(mv) > 0)
+            .map(|mv| 63 - mv.captured_square())
+            .collect();
+        squares.sort();
+        squares.dedup();
+        squares
+    }
+}
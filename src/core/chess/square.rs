@@ -0,0 +1,35 @@
+/// Names an absolute square (a1 = 0, h8 = 63), e.g. `e4`.
+pub fn square_name(square: usize) -> String {
+    format!("{}{}", file_name(square % 8), square / 8 + 1)
+}
+
+pub fn file_name(file: usize) -> char {
+    (b'a' + file as u8) as char
+}
+
+/// Parses a square name such as `e4` into an absolute square.
+pub fn parse_square(name: &str) -> Option<usize> {
+    let bytes = name.as_bytes();
+    if bytes.len() != 2 {
+        return None;
+    }
+    let file = parse_file(bytes[0] as char)?;
+    let rank = parse_rank(bytes[1] as char)?;
+    Some(rank * 8 + file)
+}
+
+pub fn parse_file(c: char) -> Option<usize> {
+    if ('a'..='h').contains(&c) {
+        Some(c as usize - 'a' as usize)
+    } else {
+        None
+    }
+}
+
+pub fn parse_rank(c: char) -> Option<usize> {
+    if ('1'..='8').contains(&c) {
+        Some(c as usize - '1' as usize)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,45 @@
+use crate::core::chess::piece::{BLACK, COLOR_BITS, PRESENT};
+
+// Castling rights and en passant are flags of the pieces themselves, so a key for every piece byte covers them. The two
+// lowest bits of a piece are never set, which leaves 64 distinct pieces.
+const PIECE_SHIFT: u32 = 2;
+const PIECE_COUNT: usize = 1 << (8 - PIECE_SHIFT);
+
+/// The key of every piece on every square, generated at compile time. Only the white ones are random: a black piece's
+/// key is the key of the white one on the opposite square rotated by half a word. Turning a board around therefore
+/// only rotates its key, which is what `Board::inverted` does on every move.
+static PIECE_KEYS: [[u64; 64]; PIECE_COUNT] = generate_piece_keys();
+
+const fn generate_piece_keys() -> [[u64; 64]; PIECE_COUNT] {
+    let mut keys = [[0; 64]; PIECE_COUNT];
+    // SplitMix64, seeded with an arbitrary constant so that keys are the same from one run to the next.
+    let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+    let mut piece = 0;
+    while piece < 256 {
+        let index = piece >> PIECE_SHIFT;
+        if piece as u8 & PRESENT == PRESENT && piece as u8 & COLOR_BITS != BLACK {
+            let mut square = 0;
+            while square < 64 {
+                state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+                let mut z = state;
+                z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+                keys[index][square] = z ^ (z >> 31);
+                keys[index | (BLACK >> PIECE_SHIFT) as usize][63 - square] = keys[index][square].rotate_left(32);
+                square += 1;
+            }
+        }
+        piece += 1 << PIECE_SHIFT;
+    }
+    keys
+}
+
+/// What `piece` on `square` adds to the key of a board. Empty squares add nothing.
+pub const fn piece_key(piece: u8, square: usize) -> u64 {
+    PIECE_KEYS[(piece >> PIECE_SHIFT) as usize][square]
+}
+
+/// The key a board seen from the other side has.
+pub const fn inverted_key(key: u64) -> u64 {
+    key.rotate_left(32)
+}
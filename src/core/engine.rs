@@ -1,4 +1,10 @@
+pub mod handle;
+pub mod alpha_beta;
+pub mod quiescence;
+pub mod move_ordering;
+pub mod transposition_table;
 pub mod evaluation_engine;
 pub mod reevaluation_engine;
 pub mod prune_engine;
+pub mod repetition;
 pub mod structs;
\ No newline at end of file
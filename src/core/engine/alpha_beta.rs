@@ -0,0 +1,298 @@
+use std::{cmp::Ordering, collections::HashSet, sync::atomic::{AtomicBool, Ordering as AtomicOrdering}, time::Instant};
+
+use crate::{core::{chess::{board::Board, board_state::{Evaluation, PositionResult}, chess_move::Move, position::Position}, engine::{move_ordering::MoveOrdering, quiescence::Quiescence, transposition_table::{Bound, TableEntry, TranspositionTable}}, search::{MAX_DEPTH, SearchInfo, SearchLimits, SearchOptions}, time_manager::TimeManager}, log};
+
+const DRAW: Evaluation = Evaluation { result: PositionResult::Draw, score: 0 };
+// Below and above every evaluation a position can have, as `compare_to` orders them.
+pub(crate) const WORST: Evaluation = Evaluation { result: PositionResult::Loss, score: i32::MAX };
+pub(crate) const BEST: Evaluation = Evaluation { result: PositionResult::Win, score: i32::MAX };
+// How many nodes are searched between looks at the clock and the stop flag.
+const CHECK_INTERVAL: usize = 1024;
+// Null-move pruning searches this many plies less deep, and one more for every six plies of depth.
+const NULL_MOVE_REDUCTION: usize = 2;
+const NULL_MOVE_MIN_DEPTH: usize = 3;
+// Late move reductions leave the first moves and the shallow searches alone.
+const LMR_MIN_DEPTH: usize = 3;
+const LMR_MIN_MOVES: usize = 3;
+// How far below alpha the static score has to be, by depth, for futility pruning and razoring. The score only counts
+// material, which a quiet move cannot change, so the margins are for what the replies may win.
+const FUTILITY_MARGINS: [i32; 3] = [0, 1, 3];
+const RAZORING_MARGINS: [i32; 3] = [0, 2, 4];
+
+/// The reductions of late move reductions by depth and by the number of the move, as `SearchOptions` sets them.
+fn late_move_reductions(options: &SearchOptions) -> Vec<[usize; 64]> {
+    (0..64).map(|depth| {
+        std::array::from_fn(|index| {
+            if depth == 0 || index == 0 {
+                return 0;
+            }
+            let reduction = options.lmr_base as f64 / 100.0
+                + (depth as f64).ln() * (index as f64).ln() / (options.lmr_divisor.max(1) as f64 / 100.0);
+            reduction as usize
+        })
+    }).collect()
+}
+
+/// The window just above `bound`, which a search only tells whether it is exceeded.
+fn just_above(bound: &Evaluation) -> Evaluation {
+    Evaluation { result: PositionResult::Scored, score: bound.score + 1 }
+}
+
+/// A bound of the parent's window seen from the child, undoing what `Evaluation::invert` does to the child's
+/// evaluation, so that the child's evaluation is above the result exactly when the inverted one is below `bound`.
+pub(crate) fn to_child(bound: &Evaluation) -> Evaluation {
+    match bound.result {
+        PositionResult::Win => Evaluation { result: PositionResult::Loss, score: (bound.score - 1).max(0) },
+        PositionResult::Loss => Evaluation { result: PositionResult::Win, score: (bound.score - 1).max(0) },
+        PositionResult::Scored => Evaluation { result: PositionResult::Scored, score: -bound.score },
+        PositionResult::Draw => *bound,
+    }
+}
+
+/// A depth-first negamax search with alpha-beta pruning. Unlike the graph search it keeps nothing but the line it is
+/// on, so it runs in constant memory whatever the depth.
+pub struct AlphaBeta<'a> {
+    game: &'a Position,
+    stop: &'a AtomicBool,
+    deadline: Option<Instant>,
+    stopped: bool,
+    nodes: usize,
+    quiescence: Quiescence,
+    table: &'a TranspositionTable,
+    ordering: MoveOrdering,
+    options: SearchOptions,
+    reductions: Vec<[usize; 64]>,
+    // The boards from the root down to the node being searched, to find repetitions within the line.
+    path: Vec<Board>,
+}
+
+impl<'a> AlphaBeta<'a> {
+    pub fn new(game: &'a Position, stop: &'a AtomicBool, deadline: Option<Instant>, options: SearchOptions, table: &'a TranspositionTable) -> Self {
+        AlphaBeta { game, stop, deadline, stopped: false, nodes: 0, quiescence: Quiescence::new(options.quiescence_checks), table, ordering: MoveOrdering::new(), options, reductions: late_move_reductions(&options), path: vec![] }
+    }
+
+    /// The positions searched so far, quiescence included.
+    pub fn nodes(&self) -> usize {
+        self.nodes + self.quiescence.nodes
+    }
+
+    /// Whether the search was stopped before it finished, in which case its result cannot be trusted.
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Searches the game's position `depth` plies deep, trying `first_move` before the others. Returns the evaluation
+    /// and the line that leads to it, which is empty when there are no legal moves.
+    pub fn search_root(&mut self, depth: usize, first_move: Option<Move>) -> (Evaluation, Vec<Move>) {
+        let mut board = self.game.board;
+        let mut principal_variation = vec![];
+        let evaluation = self.negamax(&mut board, depth, 0, WORST, BEST, first_move, None, &mut principal_variation);
+        (evaluation, principal_variation)
+    }
+
+    fn is_drawn(&self, board: &Board, ply: usize) -> bool {
+        let game_ply = self.game.history.len() + ply;
+        self.game.repeats_history(board, game_ply)
+            || self.game.exceeds_fifty_moves(board, game_ply)
+            || self.path.iter().rev().skip(1).step_by(2).any(|ancestor| ancestor.key() == board.key())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn negamax(&mut self, board: &mut Board, depth: usize, ply: usize, mut alpha: Evaluation, beta: Evaluation, first_move: Option<Move>, previous_move: Option<Move>, principal_variation: &mut Vec<Move>) -> Evaluation {
+        principal_variation.clear();
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(CHECK_INTERVAL) && (self.stop.load(AtomicOrdering::Relaxed) || self.deadline.is_some_and(|deadline| Instant::now() >= deadline)) {
+            self.stopped = true;
+        }
+        if self.stopped {
+            return alpha;
+        }
+        if ply > 0 && self.is_drawn(board, ply) {
+            return DRAW;
+        }
+        if depth == 0 {
+            return self.quiescence.search_window(board, alpha, beta);
+        }
+        let entry = self.table.probe(board.key());
+        if let Some(entry) = entry.filter(|entry| ply > 0 && entry.depth >= depth) {
+            let cuts_off = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => entry.evaluation.compare_to(&beta) != Ordering::Less,
+                Bound::Upper => entry.evaluation.compare_to(&alpha) != Ordering::Greater,
+            };
+            if cuts_off {
+                principal_variation.extend(entry.best_move);
+                return entry.evaluation;
+            }
+        }
+        let (evaluation, mut moves) = board.get_evaluation();
+        if moves.is_empty() {
+            return evaluation;
+        }
+        let in_check = board.in_check();
+        // The selective searches below only compare scores, so they keep out of the way of mates and of the root.
+        let selective = ply > 0 && !in_check && evaluation.result == PositionResult::Scored
+            && alpha.result == PositionResult::Scored && beta.result == PositionResult::Scored;
+        let frontier = selective && depth < FUTILITY_MARGINS.len();
+
+        // Razoring: far below alpha near the leaves, only the captures are likely to help.
+        if frontier && self.options.razoring && evaluation.score + RAZORING_MARGINS[depth] < alpha.score {
+            let evaluation = self.quiescence.search_window(board, alpha, beta);
+            if evaluation.compare_to(&alpha) != Ordering::Greater {
+                return evaluation;
+            }
+        }
+
+        // Null-move pruning: if passing still leaves the side to move at beta, one of its moves surely does too. It
+        // does not hold in zugzwang, where every move makes things worse, which is common with only king and pawns
+        // left. Both the root and the replies to a null move have no previous move, so two are never made in a row.
+        if selective && self.options.null_move_pruning && depth >= NULL_MOVE_MIN_DEPTH && previous_move.is_some()
+            && evaluation.compare_to(&beta) != Ordering::Less
+            && !board.side_to_move_arrangement().has_only_king_and_pawns() {
+            let reduction = NULL_MOVE_REDUCTION + depth / 6;
+            let mut null_board = board.null_move();
+            let mut null_principal_variation = vec![];
+            self.path.push(*board);
+            let below_beta = Evaluation { result: PositionResult::Scored, score: beta.score - 1 };
+            let evaluation = self.negamax(&mut null_board, depth - 1 - reduction.min(depth - 1), ply + 1, to_child(&beta), to_child(&below_beta), None, None, &mut null_principal_variation).invert();
+            self.path.pop();
+            if self.stopped {
+                return alpha;
+            }
+            if evaluation.compare_to(&beta) != Ordering::Less {
+                return beta;
+            }
+        }
+        // Futility pruning: a quiet move cannot bring the score back up to alpha from this far below it.
+        let futile = frontier && self.options.futility_pruning && evaluation.score + FUTILITY_MARGINS[depth] <= alpha.score;
+
+        // The best move of an earlier search of the position is the likeliest to be best again.
+        let hash_move = first_move.or(entry.and_then(|entry| entry.best_move));
+        self.ordering.order(board, &mut moves, hash_move, ply, previous_move);
+
+        let original_alpha = alpha;
+        self.path.push(*board);
+        let mut best: Option<Evaluation> = None;
+        let mut child_principal_variation = vec![];
+        // The quiet moves searched before the one that cuts off, if any, which get the blame for not cutting off.
+        let mut quiet_moves_tried = vec![];
+        for (index, mv) in moves.iter().enumerate() {
+            let quiet = !mv.is_capture() && !mv.is_promotion();
+            let undo = board.make_move(*mv);
+            let gives_check = board.in_check();
+            if futile && quiet && !gives_check {
+                board.unmake_move(*mv, undo);
+                continue;
+            }
+            // Late move reductions: the moves ordered late are rarely best, so they only get a shallower search that
+            // tells whether they beat alpha, and the full one if they do. The root moves all get the full search, as a
+            // quiet move that mates would otherwise only be seen depths later and by a longer mate.
+            let reduction = if self.options.late_move_reductions && ply > 0 && depth >= LMR_MIN_DEPTH && index >= LMR_MIN_MOVES
+                && quiet && !in_check && !gives_check && alpha.result == PositionResult::Scored {
+                self.reductions[depth.min(63)][index.min(63)].min(depth - 2)
+            } else {
+                0
+            };
+            let mut evaluation = alpha;
+            if reduction > 0 {
+                evaluation = self.negamax(board, depth - 1 - reduction, ply + 1, to_child(&just_above(&alpha)), to_child(&alpha), None, Some(*mv), &mut child_principal_variation).invert();
+            }
+            if reduction == 0 || (!self.stopped && evaluation.compare_to(&alpha) == Ordering::Greater) {
+                evaluation = self.negamax(board, depth - 1, ply + 1, to_child(&beta), to_child(&alpha), None, Some(*mv), &mut child_principal_variation).invert();
+            }
+            board.unmake_move(*mv, undo);
+            if self.stopped {
+                break;
+            }
+            if best.is_none_or(|best| evaluation.compare_to(&best) == Ordering::Greater) {
+                best = Some(evaluation);
+                principal_variation.clear();
+                principal_variation.push(*mv);
+                principal_variation.extend_from_slice(&child_principal_variation);
+                if evaluation.compare_to(&alpha) == Ordering::Greater {
+                    alpha = evaluation;
+                    if alpha.compare_to(&beta) != Ordering::Less {
+                        if !mv.is_capture() && !mv.is_promotion() {
+                            self.ordering.cut_off(*mv, depth, ply, previous_move, &quiet_moves_tried);
+                        }
+                        break;
+                    }
+                }
+            }
+            if quiet {
+                quiet_moves_tried.push(*mv);
+            }
+        }
+        self.path.pop();
+        if let Some(best) = best.filter(|_| !self.stopped) {
+            let bound = if best.compare_to(&original_alpha) != Ordering::Greater {
+                Bound::Upper
+            } else if best.compare_to(&beta) != Ordering::Less {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            };
+            self.table.store(board.key(), TableEntry { depth, bound, evaluation: best, best_move: principal_variation.first().copied() });
+        }
+        best.unwrap_or(alpha)
+    }
+}
+
+/// Extends `principal_variation`, which stops short wherever the search took its evaluation from the table, with the
+/// best moves the table holds for the positions that follow, up to `length` moves. It stops at a position that repeats
+/// or whose best move is not legal there, which happens when another position shares its key.
+fn extend_from_table(position: &Position, table: &TranspositionTable, principal_variation: &mut Vec<Move>, length: usize) {
+    let mut board = position.board;
+    let mut visited = HashSet::from([board.key()]);
+    for mv in principal_variation.iter() {
+        board.make_move(*mv);
+        visited.insert(board.key());
+    }
+    while principal_variation.len() < length {
+        let best_move = match table.probe(board.key()).and_then(|entry| entry.best_move) {
+            Some(best_move) if board.legal_moves().contains(&best_move) => best_move,
+            _ => break,
+        };
+        principal_variation.push(best_move);
+        board.make_move(best_move);
+        if !visited.insert(board.key()) {
+            break;
+        }
+    }
+}
+
+/// Searches `position` one depth deeper at a time until `limits` or `stop` end it, calling `report` after every
+/// completed depth. Returns the best move of the deepest completed depth, or None when there are no legal moves.
+pub fn alpha_beta_search(position: &Position, limits: SearchLimits, options: SearchOptions, table: &TranspositionTable, stop: &AtomicBool, mut report: impl FnMut(&SearchInfo)) -> Option<Move> {
+    let mut time_manager = TimeManager::new(&limits, position.side_to_move);
+    let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+    log!("Alpha-beta search started: {:?}", limits);
+    let mut alpha_beta = AlphaBeta::new(position, stop, time_manager.deadline(), options, table);
+    let mut best_move = position.legal_moves().first().copied();
+    for depth in 1..=max_depth {
+        let (evaluation, mut principal_variation) = alpha_beta.search_root(depth, best_move);
+        // A depth that was cut short has only looked at some of the moves, so its best one may be a blunder.
+        if alpha_beta.is_stopped() || principal_variation.is_empty() {
+            break;
+        }
+        best_move = principal_variation.first().copied();
+        extend_from_table(position, table, &mut principal_variation, depth);
+        report(&SearchInfo {
+            depth,
+            evaluation,
+            nodes: alpha_beta.nodes(),
+            elapsed: time_manager.elapsed(),
+            principal_variation,
+        });
+        // Nothing deeper can change a forced mate, but a draw found at this depth may be avoided by a deeper line.
+        let mate_found = matches!(evaluation.result, PositionResult::Win | PositionResult::Loss);
+        if mate_found || (!time_manager.should_deepen(best_move) && !limits.infinite) {
+            break;
+        }
+    }
+    // An infinite search only returns once it is stopped, and whoever stops it wakes this thread up.
+    while limits.infinite && !stop.load(AtomicOrdering::Relaxed) {
+        std::thread::park();
+    }
+    best_move
+}
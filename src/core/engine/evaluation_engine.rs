@@ -1,39 +1,30 @@
-use std::{collections::HashSet, sync::{Arc, RwLock, mpsc::Sender}, thread::sleep, time::{Duration, Instant}};
+use std::{collections::HashSet, sync::{Arc, RwLock, mpsc::Sender}, time::Instant};
 
 use chrono::{DateTime, Utc};
 
-use crate::{App, core::{chess::board::Board, engine::{reevaluation_engine::move_board, structs::PositionToEvaluate}, structs::map::Presence}, log};
-pub static TIMED: RwLock<bool> = RwLock::new(false);
+use crate::{core::{chess::{board::Board, board_state::{Evaluation, PositionResult}}, engine::{handle::Engine, quiescence::Quiescence, reevaluation_engine::move_board, repetition::{SearchDraw, SearchPath, mark_repeated_move, search_draw}, structs::PositionToEvaluate}, structs::map::Presence}, log};
+const DRAW: Evaluation = Evaluation { result: PositionResult::Draw, score: 0 };
 
-pub fn evaluation_engine(index: usize, run_lock: Arc<RwLock<()>>, app: App, eval_sender: Sender<(usize, Vec<PositionToEvaluate>)>) {
-    let timed: bool = *TIMED.read().unwrap();
-    // while time elapsed is less than 10 seconds
+pub fn evaluation_engine(index: usize, run_lock: Arc<RwLock<()>>, engine: Engine, eval_sender: Sender<(usize, Vec<PositionToEvaluate>)>) {
     log!("Evaluation engine started");
-    let start_time = std::time::Instant::now();
-    let positions_to_evaluate = app.positions_to_evaluate.clone();
-    let positions = app.positions.clone();
-    let positions_to_reevaluate = app.positions_to_reevaluate.clone();
+    let positions_to_evaluate = engine.positions_to_evaluate.clone();
+    let positions = engine.positions.clone();
+    let positions_to_reevaluate = engine.positions_to_reevaluate.clone();
     loop {
         // sleep(Duration::from_millis(500));
-        if timed {
-            if start_time.elapsed() > Duration::from_secs(4) {
-                log!("Evaluation engine timed out");
-                return;
-            }
-        }
         {
-            *(app.thread_stats[index].running_status.write().unwrap()) = false;
+            *(engine.thread_stats[index].running_status.write().unwrap()) = false;
         }
 
         // std::thread::sleep(Duration::from_millis(10000));
 
         {
-            *(app.thread_stats[index].running_status.write().unwrap()) = true;
+            *(engine.thread_stats[index].running_status.write().unwrap()) = true;
         }
         // println!("Evaluation engine running");
         // let position = positions_to_evaluate.dequeue(index);
         let max_depth = {
-            *app.current_depth.read().unwrap()
+            *engine.current_depth.read().unwrap()
         };
         let (board_depth, positions_to_evaluate_list) = {
             let mut c = 0;
@@ -56,14 +47,18 @@ pub fn evaluation_engine(index: usize, run_lock: Arc<RwLock<()>>, app: App, eval
                 None => continue,
             }
         };
-        let run_lock_lock = app.run_lock.read().unwrap();
+        let run_lock_lock = engine.run_lock.read().unwrap();
+        // The game only moves on while the run lock is held for writing, so it stays put for the whole batch.
+        let game = engine.current_board.read().unwrap();
+        let root_ply = *engine.root_ply.read().unwrap();
+        let mut quiescence = Quiescence::new(engine.options.read().unwrap().quiescence_checks);
         // if board_depth > 2 {
         //     continue;
         // }
         let mut skippable_set: HashSet<Board> = HashSet::new();
         for position in positions_to_evaluate_list {
-            let (previous_board, board) = position.value;
-            if let Some(previous_board) = previous_board {
+            let (previous, board) = position.value;
+            if let Some((previous_board, _)) = previous {
                 if skippable_set.contains(&previous_board) {
                     continue;
                 }
@@ -73,50 +68,82 @@ pub fn evaluation_engine(index: usize, run_lock: Arc<RwLock<()>>, app: App, eval
                     continue;
                 }
             }
+            let draw = match previous {
+                Some((previous_board, mv)) => {
+                    let draw = search_draw(&game, root_ply, &board, &position.path, board_depth);
+                    if draw == SearchDraw::RepeatsGame {
+                        mark_repeated_move(&positions, &previous_board, &mv);
+                    }
+                    draw
+                },
+                None => SearchDraw::NotDrawn,
+            };
+            // Only the draws every path to the board shares are recorded on the move reaching it.
+            let drawn = draw == SearchDraw::RepeatsGame;
             let pointer_to_board = positions.clone().edit(index, &board);
             match pointer_to_board {
                 Presence::Absent { value } => {
                     {
-                        let mut global_positions_evaluated_count = app.positions_evaluated_acount.write().unwrap();
+                        let mut global_positions_evaluated_count = engine.positions_evaluated_acount.write().unwrap();
                         *global_positions_evaluated_count = *global_positions_evaluated_count + 1;
-                        let mut positions_evaluated_length = app.thread_stats[index].positions_evaluated_length.write().unwrap();
+                        let mut positions_evaluated_length = engine.thread_stats[index].positions_evaluated_length.write().unwrap();
                         *positions_evaluated_length = *positions_evaluated_length + 1;
                     }
-                    let evaluated_board_state = board.get_evaluation();
+                    let mut evaluated_board_state = board.get_evaluation();
+                    // The children are queued in this order, so the likeliest good moves are expanded first: the move an
+                    // alpha-beta search found best, then the captures, then the quiet moves by how often they became the
+                    // best move elsewhere in the graph.
+                    let hash_move = engine.transposition_table.read().unwrap().probe(board.key()).and_then(|entry| entry.best_move);
+                    let previous_move = previous.map(|(_, mv)| mv);
+                    engine.graph_ordering.read().unwrap().order(&board, &mut evaluated_board_state.1, hash_move, board_depth, previous_move);
+                    // The material is only counted once the captures in progress have been played out.
+                    let self_evaluation = match evaluated_board_state.0.result {
+                        PositionResult::Scored => quiescence.evaluate(&board),
+                        _ => evaluated_board_state.0,
+                    };
     
                     let board_arrangement_positions = value.ptr.upgrade().unwrap();
                     let readable_board_arrangement_positions = board_arrangement_positions.read().unwrap();
                     let mut writable_board_state = readable_board_arrangement_positions.get(value.index).write().unwrap();
-                    writable_board_state.self_evaluation = evaluated_board_state.0;
-                    writable_board_state.next_moves = evaluated_board_state.1.clone().iter().map(|board| (board.clone(), None)).collect();
-                    match previous_board {
-                        Some(previous_board) => {
+                    writable_board_state.self_evaluation = self_evaluation;
+                    writable_board_state.next_moves = evaluated_board_state.1.iter().map(|mv| (*mv, None)).collect();
+                    match previous {
+                        Some((previous_board, mv)) => {
                             {
-                                writable_board_state.previous_moves.write().unwrap().insert(previous_board);
+                                writable_board_state.previous_moves.write().unwrap().insert((previous_board, mv));
                             }
                             {
-                                positions_to_reevaluate.queue(vec!((previous_board, (board, (evaluated_board_state.0, Instant::now())))));
+                                let eval = if drawn { DRAW } else { self_evaluation };
+                                positions_to_reevaluate.queue(vec!((previous_board, (mv, (eval, Instant::now())))));
                             }
                         },
                         _ => {}
                     };
                     drop(writable_board_state);
     
+                    // A draw of this path alone ends it here, while the board keeps its own evaluation for the others.
+                    if draw == SearchDraw::OnPath {
+                        continue;
+                    }
+                    let path = SearchPath::extend(&position.path, &board);
                     let mut next_boards: Vec<PositionToEvaluate> = Vec::with_capacity(evaluated_board_state.1.len());
-                    for next_board in evaluated_board_state.1 {
-                        next_boards.push(PositionToEvaluate{ value: (Some(board), next_board) });
+                    for mv in evaluated_board_state.1 {
+                        next_boards.push(PositionToEvaluate{ value: (Some((board, mv)), board.next_board(mv)), path: path.clone() });
                     }
                     // positions_to_evaluate.queue(board_depth+1, next_boards);
                     eval_sender.send((board_depth+1, next_boards)).unwrap();
                 },
                 Presence::Present { value } => {
-                    if let Some(previous_board) = previous_board {
+                    if let Some((previous_board, mv)) = previous {
                         let board_arrangement_positions = value.ptr.upgrade().unwrap();
                         let readable_board_arrangement_positions = board_arrangement_positions.read().unwrap();
                         let readable_board_state = readable_board_arrangement_positions.get(value.index).read().unwrap();
-                        readable_board_state.previous_moves.write().unwrap().insert(previous_board);
+                        readable_board_state.previous_moves.write().unwrap().insert((previous_board, mv));
                         {
                             let eval = match *readable_board_state.next_best_move.read().unwrap() {
+                                _ if drawn => {
+                                    DRAW
+                                },
                                 None => {
                                     readable_board_state.self_evaluation
                                 },
@@ -124,12 +151,13 @@ pub fn evaluation_engine(index: usize, run_lock: Arc<RwLock<()>>, app: App, eval
                                     next_best_move.evaluation
                                 }
                             };
-                            positions_to_reevaluate.queue(vec!((previous_board, (board, (eval, Instant::now())))));
+                            positions_to_reevaluate.queue(vec!((previous_board, (mv, (eval, Instant::now())))));
                         }
                     }
                 },
             }
         }
+        drop(game);
         drop(run_lock_lock);
     }
 }
\ No newline at end of file
@@ -0,0 +1,492 @@
+use std::{collections::BTreeMap, sync::{Arc, Mutex, RwLock, atomic::{AtomicBool, AtomicUsize, Ordering}, mpsc::{self, Receiver, Sender}}, thread::JoinHandle};
+
+use crate::{core::{chess::{board::Board, board_state::{Evaluation, NextBestMove}, chess_move::Move, pgn::{PgnGame, evaluation_comment}, position::Position}, engine::{alpha_beta::alpha_beta_search, evaluation_engine::evaluation_engine, move_ordering::MoveOrdering, prune_engine::prune_engine, reevaluation_engine::reevaluation_engine, structs::{PositionToEvaluate, PositionsToEvaluate, PositionsToReevaluate}, transposition_table::{DEFAULT_SIZE_MB, TranspositionTable}}, search::{SearchBackend, SearchInfo, SearchLimits, SearchOptions, search}, structs::{lock::LockWaiter, map::GroupedPositions, queue::DistributedQueue, weighted_queue::DistributedWeightedQueue}}, log};
+
+pub const INITIAL_DEPTH: usize = 5;
+
+type ProgressCallback = Box<dyn Fn(&Engine, &SearchInfo) + Send + Sync>;
+type BestMoveCallback = Box<dyn Fn(&Engine, Option<Move>) + Send + Sync>;
+
+/// The search graph, its queues and the threads evaluating them. Clones share all of it, so every thread that drives
+/// or watches the engine can hold its own.
+#[derive(Clone)]
+pub struct Engine {
+    pub(crate) current_board: Arc<RwLock<Position>>,
+    pub(crate) positions: GroupedPositions,
+    pub(crate) positions_to_evaluate: PositionsToEvaluate,
+    pub(crate) positions_to_reevaluate: PositionsToReevaluate,
+    pub(crate) run_lock: Arc<RwLock<()>>,
+    pub(crate) thread_stats: Vec<ThreadStat>,
+    pub(crate) thread_count: usize,
+    pub(crate) positions_evaluated_acount: Arc<RwLock<usize>>,
+    pub(crate) status: Arc<RwLock<String>>,
+    pub(crate) current_depth: Arc<RwLock<usize>>,
+    /// The depth the current position was queued at. The search keeps its queue as the game moves on, so this grows
+    /// with every move played from the position the search started at.
+    pub(crate) root_depth: Arc<RwLock<usize>>,
+    pub(crate) root_ply: Arc<RwLock<usize>>,
+    /// The engine's evaluation of each move of the game, from the side that played it, when it had one.
+    move_evaluations: Arc<RwLock<Vec<Option<Evaluation>>>>,
+    pub(crate) waiter: LockWaiter,
+    backend: Arc<RwLock<SearchBackend>>,
+    pub(crate) options: Arc<RwLock<SearchOptions>>,
+    /// What alpha-beta searches found, kept from one search to the next.
+    pub(crate) transposition_table: Arc<RwLock<TranspositionTable>>,
+    /// The history of the graph search, which rewards the quiet moves that become the best move of a position.
+    pub(crate) graph_ordering: Arc<RwLock<MoveOrdering>>,
+    // The size asked for while a search was using the table, which it takes once the search ends.
+    pending_hash_size: Arc<Mutex<Option<usize>>>,
+    // The position last searched by alpha-beta with what it found, since that search leaves nothing in `positions`.
+    last_search: Arc<RwLock<Option<(Board, SearchInfo)>>>,
+    engine_channels: Arc<Mutex<Option<EngineChannels>>>,
+    running_search: Arc<Mutex<Option<RunningSearch>>>,
+    search_count: Arc<AtomicUsize>,
+    progress_callbacks: Arc<RwLock<Vec<ProgressCallback>>>,
+    best_move_callbacks: Arc<RwLock<Vec<BestMoveCallback>>>,
+}
+
+impl Engine {
+
+    pub fn new(thread_count: usize) -> Engine {
+        let depth = Arc::new(RwLock::new(INITIAL_DEPTH));
+        let waiter = LockWaiter::new();
+        Engine {
+            positions: GroupedPositions::new(thread_count),
+            positions_to_evaluate: DistributedWeightedQueue::new(thread_count, depth.clone(), waiter.clone()),
+            positions_to_reevaluate: DistributedQueue::new(thread_count),
+            run_lock:  Arc::new(RwLock::new(())),
+            current_board: Arc::new(RwLock::new(Position::initial())),
+            thread_stats: (0..thread_count).map(|_| ThreadStat::new()).collect(),
+            thread_count,
+            positions_evaluated_acount: Arc::new(RwLock::new(0)),
+            status: Arc::new(RwLock::new(String::from("Evaluating..."))),
+            current_depth: depth,
+            root_depth: Arc::new(RwLock::new(0)),
+            root_ply: Arc::new(RwLock::new(0)),
+            move_evaluations: Arc::new(RwLock::new(vec![])),
+            waiter,
+            backend: Arc::new(RwLock::new(SearchBackend::default())),
+            options: Arc::new(RwLock::new(SearchOptions::default())),
+            transposition_table: Arc::new(RwLock::new(TranspositionTable::new(DEFAULT_SIZE_MB))),
+            graph_ordering: Arc::new(RwLock::new(MoveOrdering::new())),
+            pending_hash_size: Arc::new(Mutex::new(None)),
+            last_search: Arc::new(RwLock::new(None)),
+            engine_channels: Arc::new(Mutex::new(None)),
+            running_search: Arc::new(Mutex::new(None)),
+            search_count: Arc::new(AtomicUsize::new(0)),
+            progress_callbacks: Arc::new(RwLock::new(vec![])),
+            best_move_callbacks: Arc::new(RwLock::new(vec![])),
+        }
+    }
+
+    /// Starts the evaluation, prune and reevaluation engine threads, unless they are running already. From then on the
+    /// evaluation threads search the current position down to `current_depth`.
+    pub fn start_threads(&self) {
+        let mut engine_channels = self.engine_channels.lock().unwrap();
+        if engine_channels.is_some() {
+            return;
+        }
+        let engine = self.clone();
+        let _unused = std::thread::Builder::new().name(String::from("app_main")).spawn(move || {
+            engine.run_engine(engine.thread_stats.len());
+        }).unwrap();
+        let (prune_sender, prune_receiver) = mpsc::channel::<Board>();
+        let (loop_prune_sender, loop_prune_receiver) = mpsc::channel::<()>();
+        {
+            let engine = self.clone();
+            std::thread::spawn(move || {
+                prune_engine(engine, prune_receiver, loop_prune_sender);
+            });
+        }
+
+        let (reval_sender, reval_receiver) = mpsc::channel::<()>();
+        let (loop_reval_sender, loop_reval_receiver) = mpsc::channel::<()>();
+        {
+            let engine = self.clone();
+            std::thread::spawn(move || {
+                reevaluation_engine(engine, reval_receiver, loop_reval_sender);
+            });
+        }
+        *engine_channels = Some(EngineChannels { prune_sender, loop_prune_receiver, reval_sender, loop_reval_receiver });
+    }
+
+    /// Drops the positions that cannot follow `board` any more, waiting for the prune engine to finish.
+    pub fn prune(&self, board: Board) {
+        if let Some(engine_channels) = self.engine_channels.lock().unwrap().as_ref() {
+            engine_channels.prune_sender.send(board).unwrap();
+            engine_channels.loop_prune_receiver.recv().unwrap();
+        }
+    }
+
+    /// Propagates the evaluations queued so far up to the positions they were reached from, waiting for the
+    /// reevaluation engine to finish.
+    pub fn reevaluate(&self) {
+        if let Some(engine_channels) = self.engine_channels.lock().unwrap().as_ref() {
+            engine_channels.reval_sender.send(()).unwrap();
+            engine_channels.loop_reval_receiver.recv().unwrap();
+        }
+    }
+
+    pub fn position(&self) -> Position {
+        self.current_board.read().unwrap().clone()
+    }
+
+    /// Replaces the game and restarts the search from its position, discarding everything evaluated so far. A running
+    /// search is cancelled.
+    pub fn set_position(&self, position: Position) {
+        self.cancel();
+        let run_lock_lock = self.run_lock.write().unwrap();
+        log!("Setting position: {}", position.to_fen());
+        self.positions.clear();
+        self.positions_to_evaluate.clear();
+        *self.graph_ordering.write().unwrap() = MoveOrdering::new();
+        *self.root_ply.write().unwrap() = position.history.len();
+        *self.move_evaluations.write().unwrap() = vec![None; position.history.len()];
+        *self.current_depth.write().unwrap() = INITIAL_DEPTH;
+        *self.root_depth.write().unwrap() = 0;
+        self.positions_to_evaluate.queue(0, vec![PositionToEvaluate{ value: (None, position.board), path: None }]);
+        *self.current_board.write().unwrap() = position;
+        self.waiter.notify();
+        drop(run_lock_lock);
+    }
+
+    /// Moves the game on to `position`. When it continues the current game and the search has already reached it, the
+    /// search keeps what it evaluated below it. Otherwise the search starts over as with `set_position`. A running
+    /// search is cancelled.
+    pub fn continue_to(&self, position: Position) {
+        self.cancel();
+        let current_position = self.position();
+        let current_ply = current_position.history.len();
+        let continues = position.history.len() >= current_ply
+            && position.history[..current_ply] == current_position.history[..]
+            && position.history.get(current_ply).map_or(position.board, |entry| entry.board) == current_position.board;
+        if !continues || self.positions.get(&position.board).is_none() {
+            self.set_position(position);
+            return;
+        }
+        let played = position.history.len() - current_ply;
+        if played == 0 {
+            return;
+        }
+        let run_lock_lock = self.run_lock.write().unwrap();
+        log!("Continuing to position: {}", position.to_fen());
+        if current_position.board.get_board_arrangement() != position.board.get_board_arrangement() {
+            self.prune(position.board);
+        }
+        *self.root_ply.write().unwrap() = position.history.len();
+        *self.root_depth.write().unwrap() += played;
+        *self.current_depth.write().unwrap() += played;
+        self.move_evaluations.write().unwrap().resize(position.history.len(), None);
+        *self.current_board.write().unwrap() = position;
+        self.waiter.notify();
+        drop(run_lock_lock);
+    }
+
+    /// Searches the current position on a thread of its own until `limits` are reached or the search is stopped,
+    /// starting the engine threads if need be. The progress callbacks are called after every completed depth and the
+    /// best move callbacks once the search ends. A search that is still running is cancelled first.
+    pub fn start(&self, limits: SearchLimits) {
+        self.cancel();
+        self.resize_transposition_table();
+        *self.status.write().unwrap() = String::from("Evaluating...");
+        let backend = *self.backend.read().unwrap();
+        let options = *self.options.read().unwrap();
+        if backend == SearchBackend::Graph {
+            self.start_threads();
+        }
+        // The lock is held until the search is stored, so that it cannot finish and forget itself before that.
+        let mut running_search = self.running_search.lock().unwrap();
+        let id = self.search_count.fetch_add(1, Ordering::Relaxed);
+        let stop = Arc::new(AtomicBool::new(false));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let engine = self.clone();
+            let stop = stop.clone();
+            let cancelled = cancelled.clone();
+            std::thread::Builder::new().name(String::from("search")).spawn(move || {
+                let report = |info: &SearchInfo| {
+                    for progress_callback in engine.progress_callbacks.read().unwrap().iter() {
+                        progress_callback(&engine, info);
+                    }
+                };
+                let best_move = match backend {
+                    SearchBackend::Graph => search(&engine, limits, &stop, report),
+                    SearchBackend::AlphaBeta => {
+                        let position = engine.position();
+                        let table = engine.transposition_table.read().unwrap();
+                        table.new_search();
+                        let best_move = alpha_beta_search(&position, limits, options, &table, &stop, |info| {
+                            *engine.last_search.write().unwrap() = Some((position.board, info.clone()));
+                            report(info);
+                        });
+                        drop(table);
+                        engine.resize_transposition_table();
+                        best_move
+                    },
+                };
+                // Whatever the callbacks do to the engine must not wait for this search to end.
+                let mut running_search = engine.running_search.lock().unwrap();
+                if running_search.as_ref().is_some_and(|running_search| running_search.id == id) {
+                    *running_search = None;
+                }
+                drop(running_search);
+                if !cancelled.load(Ordering::Relaxed) {
+                    for best_move_callback in engine.best_move_callbacks.read().unwrap().iter() {
+                        best_move_callback(&engine, best_move);
+                    }
+                }
+            }).unwrap()
+        };
+        *running_search = Some(RunningSearch { id, stop, cancelled, handle });
+    }
+
+    /// Stops the running search and waits for the best move callbacks to be called.
+    pub fn stop(&self) {
+        let running_search = self.running_search.lock().unwrap().take();
+        if let Some(running_search) = running_search {
+            running_search.stop.store(true, Ordering::Relaxed);
+            // An infinite search that has gone as deep as it can waits to be woken up.
+            running_search.handle.thread().unpark();
+            running_search.handle.join().unwrap();
+        }
+    }
+
+    /// Stops the running search without calling the best move callbacks.
+    pub fn cancel(&self) {
+        let running_search = self.running_search.lock().unwrap().take();
+        if let Some(running_search) = running_search {
+            running_search.cancelled.store(true, Ordering::Relaxed);
+            running_search.stop.store(true, Ordering::Relaxed);
+            running_search.handle.thread().unpark();
+            running_search.handle.join().unwrap();
+        }
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.running_search.lock().unwrap().as_ref().is_some_and(|running_search| !running_search.handle.is_finished())
+    }
+
+    /// Called with the searched position's progress after every depth the search completes.
+    pub fn on_progress(&self, callback: impl Fn(&Engine, &SearchInfo) + Send + Sync + 'static) {
+        self.progress_callbacks.write().unwrap().push(Box::new(callback));
+    }
+
+    /// Called with the move to play when a search ends, or None when the position has no legal moves.
+    pub fn on_best_move(&self, callback: impl Fn(&Engine, Option<Move>) + Send + Sync + 'static) {
+        self.best_move_callbacks.write().unwrap().push(Box::new(callback));
+    }
+
+    pub fn backend(&self) -> SearchBackend {
+        *self.backend.read().unwrap()
+    }
+
+    /// Chooses how the next searches are run. A running search carries on as it started.
+    pub fn set_backend(&self, backend: SearchBackend) {
+        *self.backend.write().unwrap() = backend;
+    }
+
+    pub fn options(&self) -> SearchOptions {
+        *self.options.read().unwrap()
+    }
+
+    /// Changes the options of the next searches. A running search carries on with the options it started with.
+    pub fn update_options(&self, update: impl FnOnce(&mut SearchOptions)) {
+        update(&mut self.options.write().unwrap());
+    }
+
+    /// Replaces the transposition table with an empty one of `size_mb` megabytes. A running search keeps the old one
+    /// until it ends, so that this never waits for it. Only alpha-beta is bounded by this size, as the graph search
+    /// keeps every position it reaches until they are pruned.
+    pub fn set_hash_size(&self, size_mb: usize) {
+        *self.pending_hash_size.lock().unwrap() = Some(size_mb);
+        self.resize_transposition_table();
+    }
+
+    /// The size of the transposition table in megabytes, or the size it takes once the running search ends.
+    pub fn hash_size(&self) -> usize {
+        self.pending_hash_size.lock().unwrap().unwrap_or_else(|| self.transposition_table.read().unwrap().size_mb())
+    }
+
+    /// Forgets what the earlier searches stored in the transposition table.
+    pub fn clear_hash(&self) {
+        self.transposition_table.read().unwrap().clear();
+    }
+
+    fn resize_transposition_table(&self) {
+        let mut pending_hash_size = self.pending_hash_size.lock().unwrap();
+        let Some(size_mb) = *pending_hash_size else {
+            return;
+        };
+        // A search holding the table resizes it when it lets go of it.
+        if let Ok(mut transposition_table) = self.transposition_table.try_write() {
+            if transposition_table.size_mb() != size_mb {
+                *transposition_table = TranspositionTable::new(size_mb);
+            }
+            *pending_hash_size = None;
+        }
+    }
+
+    /// The evaluation `mv` got when the search looked at it in `board`, from the side that plays it.
+    pub fn move_evaluation(&self, board: &Board, mv: Move) -> Option<Evaluation> {
+        let pointer_to_board = self.positions.get(board)?;
+        let board_arrangement_positions = pointer_to_board.ptr.upgrade()?;
+        let readable_board_arrangement_positions = board_arrangement_positions.read().unwrap();
+        let board_state = readable_board_arrangement_positions.get(pointer_to_board.index).read().unwrap();
+        board_state.next_moves.iter().find(|next_move| next_move.0 == mv)?.1.map(|(evaluation, _)| evaluation.invert())
+    }
+
+    /// Records what the last move of the game was worth to the side that played it, for `to_pgn`.
+    pub fn set_last_move_evaluation(&self, evaluation: Option<Evaluation>) {
+        if let Some(last_evaluation) = self.move_evaluations.write().unwrap().last_mut() {
+            *last_evaluation = evaluation;
+        }
+    }
+
+    /// Lets the graph search look `INITIAL_DEPTH` plies below the current position while no search is running, so
+    /// that the next search starts from there.
+    pub fn think_ahead(&self) {
+        *self.current_depth.write().unwrap() = *self.root_depth.read().unwrap() + INITIAL_DEPTH;
+        self.waiter.notify();
+    }
+
+    pub fn thread_stats(&self) -> &[ThreadStat] {
+        &self.thread_stats
+    }
+
+    /// How many boards of the graph each thread's share holds, as `{a, b, ...}`.
+    pub fn position_counts(&self) -> String {
+        self.positions.len()
+    }
+
+    /// How many positions the evaluation threads have evaluated since the engine started.
+    pub fn positions_evaluated(&self) -> usize {
+        *self.positions_evaluated_acount.read().unwrap()
+    }
+
+    /// How many positions wait to be evaluated at each depth.
+    pub fn evaluation_queue_lengths(&self) -> BTreeMap<usize, usize> {
+        self.positions_to_evaluate.lengths()
+    }
+
+    /// How many evaluations wait to be propagated to the positions they were reached from.
+    pub fn reevaluation_queue_length(&self) -> usize {
+        self.positions_to_reevaluate.queues.iter().map(|queue| *queue.length.read().unwrap()).sum()
+    }
+
+    /// What the engine threads are busy with.
+    pub fn status(&self) -> String {
+        self.status.read().unwrap().clone()
+    }
+
+    /// The move the search currently prefers in the current position.
+    pub fn best_move(&self) -> Option<Move> {
+        self.principal_variation(1).first().copied()
+    }
+
+    /// The move the search currently prefers in `board`, if it has evaluated any of its moves.
+    pub fn next_best_move(&self, board: &Board) -> Option<NextBestMove> {
+        let pointer_to_board = self.positions.get(board)?;
+        let board_arrangement_positions = pointer_to_board.ptr.upgrade()?;
+        let readable_board_arrangement_positions = board_arrangement_positions.read().unwrap();
+        let board_state = readable_board_arrangement_positions.get(pointer_to_board.index).read().unwrap();
+        *board_state.next_best_move.read().unwrap()
+    }
+
+    /// The line the search expects from the current position, for at most `length` moves. The graph search's line
+    /// follows the best moves until a position repeats or has no best move yet.
+    pub fn principal_variation(&self, length: usize) -> Vec<Move> {
+        let position = self.position();
+        if *self.backend.read().unwrap() == SearchBackend::AlphaBeta {
+            return match self.last_search.read().unwrap().as_ref() {
+                Some((board, info)) if *board == position.board => info.principal_variation.iter().take(length).copied().collect(),
+                _ => vec![],
+            };
+        }
+        position.follow_line(length, |board| self.next_best_move(board).map(|next_best_move| next_best_move.mv))
+    }
+
+    /// The game so far, with the engine's evaluations as `[%eval]` comments.
+    pub fn to_pgn(&self, position: &Position) -> PgnGame {
+        let mut game = PgnGame::from_position(position);
+        game.set_tag("Event", "Casual game");
+        game.set_tag("Date", &chrono::Local::now().format("%Y.%m.%d").to_string());
+        let move_count = game.moves.len();
+        let move_evaluations = self.move_evaluations.read().unwrap();
+        for (ply, (pgn_move, evaluation)) in game.moves.iter_mut().zip(move_evaluations.iter()).enumerate() {
+            // The last move was played by the side that is not to move.
+            let color = if (move_count - ply).is_multiple_of(2) { position.side_to_move } else { position.side_to_move.opposite() };
+            if let Some(evaluation) = evaluation {
+                pgn_move.comments.push(evaluation_comment(evaluation, color));
+            }
+        }
+        game
+    }
+
+    fn run_engine(&self, thread_count: usize) {
+        log!("Running engine");
+        let root_board = {
+            let current_position = self.current_board.read().unwrap();
+            *self.root_ply.write().unwrap() = current_position.history.len();
+            current_position.board
+        };
+        self.positions_to_evaluate.queue(0, vec![PositionToEvaluate{ value: (None, root_board), path: None }]);
+        log!("queued");
+        let mut threads: Vec<JoinHandle<()>> = Vec::new();
+        log!("Starting {} threads", thread_count);
+        let (eval_sender, eval_receiver) = mpsc::channel::<(usize, Vec<PositionToEvaluate>)>();
+        let q = self.positions_to_evaluate.clone();
+        std::thread::Builder::new().name(String::from("eval_queuer")).spawn(move || {
+            loop {
+                let value = eval_receiver.recv().unwrap();
+                q.queue(value.0, value.1);
+            }
+        }).unwrap();
+        for i  in 0..self.thread_stats.len() {
+            let engine = self.clone();
+            let run_lock = self.run_lock.clone();
+            let eval_sender = eval_sender.clone();
+            let join_handle = std::thread::Builder::new().name(format!("evaluation_engine_{}", i)).spawn(move || {
+                evaluation_engine(i, run_lock, engine, eval_sender.clone());
+            }).unwrap();
+            threads.push(join_handle);
+        }
+        log!("threads started");
+    }
+}
+
+/// Hands boards to the prune engine and wakes up the reevaluation engine, each answering once it is done.
+struct EngineChannels {
+    prune_sender: Sender<Board>,
+    loop_prune_receiver: Receiver<()>,
+    reval_sender: Sender<()>,
+    loop_reval_receiver: Receiver<()>,
+}
+
+/// A search running on its own thread, so that whoever started it can carry on.
+struct RunningSearch {
+    id: usize,
+    stop: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+#[derive(Clone)]
+pub struct ThreadStat{
+    pub positions_evaluated_length: Arc<RwLock<usize>>,
+    pub running_status: Arc<RwLock<bool>>,
+}
+
+impl Default for ThreadStat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ThreadStat {
+    pub fn new() -> Self {
+        ThreadStat {
+            positions_evaluated_length: Arc::new(RwLock::new(0)),
+            running_status: Arc::new(RwLock::new(false)),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crate::core::chess::{board::Board, chess_move::Move, piece::{BISHOP, KING, KNIGHT, PAWN, PRESENT, QUEEN, ROOK, get_material_value, get_presence, get_type}};
+
+// The scores that put each kind of move ahead of the next: the hash move, captures and promotions that do not lose
+// material, killers, countermoves, the captures that do and then the quiet moves by their history, which stays within
+// `HISTORY_MAX` either way.
+const HASH_MOVE: i32 = i32::MAX;
+const CAPTURE: i32 = 1 << 20;
+const KILLER: i32 = 1 << 19;
+const COUNTERMOVE: i32 = 1 << 18;
+const LOSING_CAPTURE: i32 = 1 << 17;
+const HISTORY_MAX: i32 = 1 << 14;
+
+/// Most valuable victim, least valuable attacker: the bigger the piece taken the better, and for the same piece the
+/// smaller the one taking it, as it has the least to lose if the square is defended. Promotions count the piece the
+/// pawn becomes as taken.
+pub fn mvv_lva(board: &Board, mv: &Move) -> i32 {
+    let captured = board.pieces()[mv.captured_square()];
+    let victim = if mv.is_capture() && get_presence(captured) == PRESENT { get_material_value(captured) as i32 } else { 0 };
+    let promotion = if mv.is_promotion() { get_material_value(mv.promotion) as i32 - 1 } else { 0 };
+    let attacker = match get_type(board.pieces()[mv.from as usize]) {
+        PAWN => 1,
+        KNIGHT => 2,
+        BISHOP => 3,
+        ROOK => 4,
+        QUEEN => 5,
+        KING => 6,
+        _ => 0,
+    };
+    (victim + promotion) * 8 - attacker
+}
+
+/// Whether `mv` loses material once the exchange it starts is played out. Taking a piece worth at least the one taking
+/// it cannot, which spares most captures the exchange evaluation.
+pub fn loses_exchange(board: &Board, mv: &Move) -> bool {
+    let captured = board.pieces()[mv.captured_square()];
+    let victim = if mv.is_capture() && get_presence(captured) == PRESENT { get_material_value(captured) } else { 0 };
+    let attacker = board.pieces()[mv.from as usize];
+    get_type(attacker) != KING && get_material_value(attacker) > victim && board.see(mv) < 0
+}
+
+/// Sorts `moves` by what can be told of them without searching: `hash_move` first, then captures and promotions by
+/// `mvv_lva`, those that lose material in the exchange after those that do not, then the rest in the order they were
+/// generated.
+pub fn order_moves(board: &Board, moves: &mut [Move], hash_move: Option<Move>) {
+    moves.sort_by_cached_key(|mv| -static_score(board, mv, hash_move));
+}
+
+fn static_score(board: &Board, mv: &Move, hash_move: Option<Move>) -> i32 {
+    if Some(*mv) == hash_move {
+        HASH_MOVE
+    } else if mv.is_capture() || mv.is_promotion() {
+        if loses_exchange(board, mv) { LOSING_CAPTURE + mvv_lva(board, mv) } else { CAPTURE + mvv_lva(board, mv) }
+    } else {
+        0
+    }
+}
+
+/// What a search learns about the quiet moves as it goes, to try the ones that cut off elsewhere first. The boards
+/// are seen from the side to move, so the squares of a move mean the same for both sides and the tables are shared.
+pub struct MoveOrdering {
+    // The last two quiet moves that cut off at each ply, the latest first.
+    killers: Vec<[Option<Move>; 2]>,
+    // How often a quiet move from one square to another cut off, less how often it was tried and did not.
+    history: Box<[[i32; 64]; 64]>,
+    // The quiet move that last cut off in reply to the opponent's move between two squares.
+    countermoves: Box<[[Option<Move>; 64]; 64]>,
+}
+
+impl Default for MoveOrdering {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MoveOrdering {
+    pub fn new() -> Self {
+        MoveOrdering { killers: vec![], history: Box::new([[0; 64]; 64]), countermoves: Box::new([[None; 64]; 64]) }
+    }
+
+    /// Sorts `moves` for the node `ply` plies below the root, reached by `previous_move`.
+    pub fn order(&self, board: &Board, moves: &mut [Move], hash_move: Option<Move>, ply: usize, previous_move: Option<Move>) {
+        let killers = self.killers.get(ply).copied().unwrap_or_default();
+        let countermove = previous_move.and_then(|previous_move| self.countermoves[previous_move.from as usize][previous_move.to as usize]);
+        moves.sort_by_cached_key(|mv| {
+            let score = static_score(board, mv, hash_move);
+            if score >= CAPTURE {
+                -score
+            } else if Some(*mv) == killers[0] {
+                -(KILLER + 1)
+            } else if Some(*mv) == killers[1] {
+                -KILLER
+            } else if Some(*mv) == countermove {
+                -COUNTERMOVE
+            } else if score != 0 {
+                -score
+            } else {
+                -self.history[mv.from as usize][mv.to as usize]
+            }
+        });
+    }
+
+    /// Records that the quiet move `mv` cut off a search `depth` plies deep, after `tried` had been searched in vain.
+    pub fn cut_off(&mut self, mv: Move, depth: usize, ply: usize, previous_move: Option<Move>, tried: &[Move]) {
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None; 2]);
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(mv) {
+            killers[1] = killers[0];
+            killers[0] = Some(mv);
+        }
+        if let Some(previous_move) = previous_move {
+            self.countermoves[previous_move.from as usize][previous_move.to as usize] = Some(mv);
+        }
+        let bonus = (depth * depth).min(HISTORY_MAX as usize) as i32;
+        self.update_history(&mv, bonus);
+        for tried in tried {
+            self.update_history(tried, -bonus);
+        }
+    }
+
+    /// Records that the quiet move `mv` became the best move of a position of the graph search, which has no
+    /// cut-offs to learn from.
+    pub fn best_move_found(&mut self, mv: Move) {
+        self.update_history(&mv, 1);
+    }
+
+    fn update_history(&mut self, mv: &Move, bonus: i32) {
+        // Moving towards the bound by the part of the bonus that is left keeps the score within it, so that old
+        // cut-offs fade as new ones come.
+        let history = &mut self.history[mv.from as usize][mv.to as usize];
+        *history += bonus - *history * bonus.abs() / HISTORY_MAX;
+    }
+}
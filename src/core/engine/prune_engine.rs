@@ -1,13 +1,13 @@
 use std::{sync::mpsc::{self, Receiver, Sender}, thread::sleep, time::Duration};
 
-use crate::{App, core::{chess::board::{Board, BoardArrangement, can_come_after_board_arrangement}, engine::reevaluation_engine::move_board_arrangement, structs::map::Positions}, log};
+use crate::{core::{chess::board::{Board, BoardArrangement, can_come_after_board_arrangement}, engine::handle::Engine, structs::map::Positions}, log};
 
-pub fn prune_engine(app: App, receiver: Receiver<Board>, sender: Sender<()>) {
+pub fn prune_engine(engine: Engine, receiver: Receiver<Board>, sender: Sender<()>) {
 
     let mut handles = vec![];
     let mut wakers: Vec<(Sender<Board>, Receiver<()>)> = vec![];
-    for i in 0..app.thread_count {
-        let map = app.positions.map[i].clone().unwrap();
+    for i in 0..engine.thread_count {
+        let map = engine.positions.map[i].clone().unwrap();
         let (self_tx, self_rx) = mpsc::channel();
         let (thread_tx, thread_rx) = mpsc::channel();
         handles.push(std::thread::Builder::new().name(format!("prune_engine_checker_{}", i)).spawn(move || {
@@ -20,11 +20,11 @@ pub fn prune_engine(app: App, receiver: Receiver<Board>, sender: Sender<()>) {
         let next_board = receiver.recv().unwrap();
         log!("Pruning engine started");
         {
-            let app = app.clone();
-            *app.status.write().unwrap() = String::from("Pruning positions...");
+            let engine = engine.clone();
+            *engine.status.write().unwrap() = String::from("Pruning positions...");
         }
         for (thread_tx, _) in wakers.iter() {
-            thread_tx.send(next_board.clone()).unwrap();
+            thread_tx.send(next_board).unwrap();
         }
         for (_, self_rx) in wakers.iter() {
             self_rx.recv().unwrap();
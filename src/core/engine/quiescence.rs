@@ -0,0 +1,94 @@
+use std::cmp::Ordering;
+
+use crate::core::{chess::{board::Board, board_state::{Evaluation, PositionResult}, chess_move::Move, piece::{get_material_value, get_presence, PRESENT}}, engine::{alpha_beta::{BEST, WORST, to_child}, move_ordering::{loses_exchange, mvv_lva}}};
+
+// What a capture may gain beyond the piece it takes, through the positional swings the static score cannot see,
+// before it is considered too small to bring the score back up to alpha.
+const DELTA_MARGIN: i32 = 2;
+
+/// Resolves the captures and promotions of a position before it is scored, so that a leaf in the middle of an
+/// exchange is not taken at its material count. The side to move may always stand pat on the static score instead of
+/// capturing, except when in check, where every evasion is searched.
+pub struct Quiescence {
+    /// Whether moves that give check are also tried, on the first ply only so that the search still ends.
+    pub checks: bool,
+    /// The positions searched beyond the ones that were asked to be evaluated.
+    pub nodes: usize,
+}
+
+impl Quiescence {
+    pub fn new(checks: bool) -> Self {
+        Quiescence { checks, nodes: 0 }
+    }
+
+    /// The evaluation of `board` once the captures have been played out, from the side to move.
+    pub fn evaluate(&mut self, board: &Board) -> Evaluation {
+        let mut board = *board;
+        self.search(&mut board, 0, WORST, BEST)
+    }
+
+    /// `evaluate` for a leaf of a search with the window `alpha`..`beta`, whose result is only exact inside of it.
+    pub fn search_window(&mut self, board: &mut Board, alpha: Evaluation, beta: Evaluation) -> Evaluation {
+        self.search(board, 0, alpha, beta)
+    }
+
+    fn search(&mut self, board: &mut Board, ply: usize, mut alpha: Evaluation, beta: Evaluation) -> Evaluation {
+        let (stand_pat, moves) = board.get_evaluation();
+        if stand_pat.result != PositionResult::Scored {
+            return stand_pat;
+        }
+        let in_check = board.in_check();
+        let mut best = None;
+        if !in_check {
+            if stand_pat.compare_to(&beta) != Ordering::Less {
+                return stand_pat;
+            }
+            if stand_pat.compare_to(&alpha) == Ordering::Greater {
+                alpha = stand_pat;
+            }
+            best = Some(stand_pat);
+        }
+
+        let mut moves: Vec<Move> = moves.into_iter()
+            .filter(|mv| in_check || mv.is_capture() || mv.is_promotion() || (self.checks && ply == 0 && board.next_board(*mv).in_check()))
+            .collect();
+        // The biggest captures by the smallest pieces first, as they are the likeliest to cut the rest off.
+        moves.sort_by_cached_key(|mv| -mvv_lva(board, mv));
+        for mv in moves {
+            if !in_check && !mv.is_promotion() {
+                // A capture that loses material once the square is taken back cannot raise the score.
+                if loses_exchange(board, &mv) {
+                    continue;
+                }
+                // Delta pruning: even winning the piece for nothing would leave the score below alpha.
+                let optimistic = Evaluation { result: PositionResult::Scored, score: stand_pat.score + gain(board, &mv) + DELTA_MARGIN };
+                if optimistic.compare_to(&alpha) != Ordering::Greater {
+                    continue;
+                }
+            }
+            self.nodes += 1;
+            let undo = board.make_move(mv);
+            let evaluation = self.search(board, ply + 1, to_child(&beta), to_child(&alpha)).invert();
+            board.unmake_move(mv, undo);
+            if best.is_none_or(|best| evaluation.compare_to(&best) == Ordering::Greater) {
+                best = Some(evaluation);
+                if evaluation.compare_to(&alpha) == Ordering::Greater {
+                    alpha = evaluation;
+                    if alpha.compare_to(&beta) != Ordering::Less {
+                        break;
+                    }
+                }
+            }
+        }
+        // Standing pat sets `best` outside of check, and a position in check that is not mate has an evasion to search.
+        best.unwrap_or(alpha)
+    }
+}
+
+/// The material `mv` wins on `board`: the piece it takes and what a pawn gains by promoting.
+fn gain(board: &Board, mv: &Move) -> i32 {
+    let captured = board.pieces()[mv.captured_square()];
+    let captured_value = if mv.is_capture() && get_presence(captured) == PRESENT { get_material_value(captured) } else { 0 };
+    let promotion_value = if mv.is_promotion() { get_material_value(mv.promotion) - 1 } else { 0 };
+    (captured_value + promotion_value) as i32
+}
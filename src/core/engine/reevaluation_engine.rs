@@ -1,22 +1,23 @@
-use std::{cmp::Ordering, sync::{RwLock, mpsc::{self, Receiver, Sender}}, thread::sleep, time::{Duration, Instant}};
+use std::{cmp::Ordering, sync::{Arc, RwLock, mpsc::{self, Receiver, Sender}}, thread::sleep, time::{Duration, Instant}};
 
-use crate::{core::{app::App, chess::{board::{Board, BoardArrangement}, board_state::NextBestMove}, engine::structs::{PositionToReevaluate, PositionsToReevaluate}, structs::map::{GroupedPositions, Positions}}, log};
+use crate::{core::{chess::{board::{Board, BoardArrangement}, board_state::{Evaluation, NextBestMove, PositionResult}}, engine::{handle::Engine, move_ordering::MoveOrdering, structs::{PositionToReevaluate, PositionsToReevaluate}}, structs::map::{GroupedPositions, Positions}}, log};
 use std::sync::LazyLock;
 
 pub static move_board: LazyLock<RwLock<Board>> = LazyLock::new(|| RwLock::new(Board::new()));
 pub static move_board_arrangement: LazyLock<RwLock<BoardArrangement>> = LazyLock::new(|| RwLock::new(BoardArrangement::new()));
 
-pub fn reevaluation_engine(app: App, receiver: Receiver<()>, sender: Sender<()>) {
+pub fn reevaluation_engine(engine: Engine, receiver: Receiver<()>, sender: Sender<()>) {
     let mut handles = vec![];
     let mut wakers: Vec<(Sender<()>, Receiver<()>)> = vec![];
-    let app= app.clone();
-    for i in 0..app.thread_count {
-        let positions_to_reevaluate = app.positions_to_reevaluate.clone();
-        let positions = app.positions.clone();
+    let engine= engine.clone();
+    for i in 0..engine.thread_count {
+        let positions_to_reevaluate = engine.positions_to_reevaluate.clone();
+        let positions = engine.positions.clone();
+        let ordering = engine.graph_ordering.clone();
         let (self_tx, self_rx) = mpsc::channel();
         let (thread_tx, thread_rx) = mpsc::channel();
         handles.push(std::thread::Builder::new().name(format!("reevaluation_engine_{}", i)).spawn(move || {
-            reevaluation_thread(positions_to_reevaluate, positions, i, thread_rx, self_tx);
+            reevaluation_thread(positions_to_reevaluate, positions, ordering, i, thread_rx, self_tx);
         }));
         wakers.push((thread_tx, self_rx));
     }
@@ -24,8 +25,8 @@ pub fn reevaluation_engine(app: App, receiver: Receiver<()>, sender: Sender<()>)
     loop {
         let _ = receiver.recv().unwrap();
         {
-            let app = app.clone();
-            *app.status.write().unwrap() = String::from("Re-evaluating positions...");
+            let engine = engine.clone();
+            *engine.status.write().unwrap() = String::from("Re-evaluating positions...");
         }
         for (thread_tx, _) in wakers.iter() {
             thread_tx.send(()).unwrap();
@@ -37,7 +38,7 @@ pub fn reevaluation_engine(app: App, receiver: Receiver<()>, sender: Sender<()>)
     }
 }
 
-pub fn reevaluation_thread(positions_to_reevaluate: PositionsToReevaluate, positions: GroupedPositions, index: usize, receiver: Receiver<()>, sender: Sender<()>) {
+pub fn reevaluation_thread(positions_to_reevaluate: PositionsToReevaluate, positions: GroupedPositions, ordering: Arc<RwLock<MoveOrdering>>, index: usize, receiver: Receiver<()>, sender: Sender<()>) {
     loop {
         let _ = receiver.recv().unwrap();
         loop {
@@ -64,9 +65,9 @@ pub fn reevaluation_thread(positions_to_reevaluate: PositionsToReevaluate, posit
                 None => break,
             };
 
-            for (board_to_reevaluate, (next_board, (next_board_new_evaluation, next_board_new_evaluation_timestamp))) in value {
+            for (board_to_reevaluate, (mv, (next_board_new_evaluation, next_board_new_evaluation_timestamp))) in value {
                 if board_to_reevaluate == *move_board.read().unwrap() {
-                    log!("Checking move: {}", next_board);
+                    log!("Checking move: {}", mv);
                     log!("Move eval: {}", next_board_new_evaluation);
                 }
                 if let Some(pointer_to_board) = positions.get(&board_to_reevaluate) {
@@ -74,12 +75,18 @@ pub fn reevaluation_thread(positions_to_reevaluate: PositionsToReevaluate, posit
                     if let Some(board_arrangement_positions) = board_arrangement_positions {
                         let readable_board_arrangement_positions = board_arrangement_positions.read().unwrap();
                         let mut board_state = readable_board_arrangement_positions.get(pointer_to_board.index).write().unwrap();
+                        // Moves that repeat a position of the game stay draws whatever the repeated position is worth on its own.
+                        let next_board_new_evaluation = if board_state.repeated_moves.read().unwrap().contains(&mv) {
+                            Evaluation { result: PositionResult::Draw, score: 0 }
+                        } else {
+                            next_board_new_evaluation
+                        };
                         let next_moves = &mut board_state.next_moves;
 
                         let mut should_reevaluate = false;
                         for i in 0..next_moves.len() {
                             let next_position = next_moves[i];
-                            if next_position.0 == next_board {
+                            if next_position.0 == mv {
                                 if (next_position.1.is_none() || next_position.1.unwrap().1 < next_board_new_evaluation_timestamp) {
                                     next_moves[i].1 = Some((next_board_new_evaluation, next_board_new_evaluation_timestamp));
                                     should_reevaluate = true;
@@ -94,11 +101,11 @@ pub fn reevaluation_thread(positions_to_reevaluate: PositionsToReevaluate, posit
                                 let next_position_evaluation_inverted = next_position_evaluation.invert();
                                 match best_move {
                                     None => {
-                                        best_move = Some(NextBestMove{board: next_move.0, evaluation: next_position_evaluation_inverted});
+                                        best_move = Some(NextBestMove{mv: next_move.0, evaluation: next_position_evaluation_inverted});
                                     },
                                     Some(present_best_move) => {
                                         if next_position_evaluation_inverted.compare_to(&present_best_move.evaluation) == Ordering::Greater {
-                                            best_move = Some(NextBestMove{board: next_move.0, evaluation: next_position_evaluation_inverted});
+                                            best_move = Some(NextBestMove{mv: next_move.0, evaluation: next_position_evaluation_inverted});
                                         }
                                     }
                                 }
@@ -108,9 +115,13 @@ pub fn reevaluation_thread(positions_to_reevaluate: PositionsToReevaluate, posit
                         let mut current_next_best_move = board_state.next_best_move.write().unwrap();
                         if let Some(best_move) = best_move {
                             if current_next_best_move.is_none() || current_next_best_move.unwrap() != best_move {
+                                let quiet = !best_move.mv.is_capture() && !best_move.mv.is_promotion();
+                                if quiet && current_next_best_move.is_none_or(|current| current.mv != best_move.mv) {
+                                    ordering.write().unwrap().best_move_found(best_move.mv);
+                                }
                                 *current_next_best_move = Some(best_move);
-                                let queue: Vec<PositionToReevaluate> = board_state.previous_moves.read().unwrap().iter().map(|previous_board| {
-                                    (*previous_board, (board_to_reevaluate, (best_move.evaluation, Instant::now())))
+                                let queue: Vec<PositionToReevaluate> = board_state.previous_moves.read().unwrap().iter().map(|(previous_board, previous_mv)| {
+                                    (*previous_board, (*previous_mv, (best_move.evaluation, Instant::now())))
                                 }).collect();
                                 positions_to_reevaluate.queue(queue);
                             }
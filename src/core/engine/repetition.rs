@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use crate::core::{chess::{board::Board, chess_move::Move, position::Position}, structs::map::GroupedPositions};
+
+/// The boards the graph search went through from its root to reach a position, the latest first. The children of a
+/// board share its path, so each board expanded adds a single link.
+pub struct SearchPath {
+    key: u64,
+    previous: Option<Arc<SearchPath>>,
+}
+
+impl SearchPath {
+    /// The path to the children of `board`, reached through `path`.
+    pub fn extend(path: &Option<Arc<SearchPath>>, board: &Board) -> Option<Arc<SearchPath>> {
+        Some(Arc::new(SearchPath { key: board.key(), previous: path.clone() }))
+    }
+
+    /// Whether `board`, played at the end of `path`, repeats one of its boards with the same side to move.
+    pub fn repeats(path: &Option<Arc<SearchPath>>, board: &Board) -> bool {
+        let mut link = path.as_deref();
+        let mut distance = 1;
+        while let Some(ancestor) = link {
+            if distance % 2 == 0 && ancestor.key == board.key() {
+                return true;
+            }
+            link = ancestor.previous.as_deref();
+            distance += 1;
+        }
+        false
+    }
+}
+
+/// How a board reached by the search draws the game.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SearchDraw {
+    NotDrawn,
+    /// It repeats a position of the game, which draws it whichever path the search took to it, so the move reaching it
+    /// can be scored as a draw in the positions the paths share.
+    RepeatsGame,
+    /// It repeats a board of the path or reaches the fifty-move limit on it. Other paths to the same board can be worth
+    /// more, so only this path ends there and the shared positions are left as they are.
+    OnPath,
+}
+
+/// How `board`, played at the end of `path` `depth` plies below the search root, draws the game. `root_ply` is the ply
+/// of the game at the search root.
+pub fn search_draw(game: &Position, root_ply: usize, board: &Board, path: &Option<Arc<SearchPath>>, depth: usize) -> SearchDraw {
+    if game.repeats_history(board, root_ply + depth) {
+        SearchDraw::RepeatsGame
+    } else if game.exceeds_fifty_moves(board, root_ply + depth) || SearchPath::repeats(path, board) {
+        SearchDraw::OnPath
+    } else {
+        SearchDraw::NotDrawn
+    }
+}
+
+/// Records on `previous_board` that `mv` is a draw, so that the reevaluation engine keeps scoring it as one. Only for
+/// moves that repeat a position of the game, which draws them whichever path the search took to `previous_board`.
+/// None of the positions' locks may be held by the caller.
+pub fn mark_repeated_move(positions: &GroupedPositions, previous_board: &Board, mv: &Move) {
+    if let Some(pointer_to_board) = positions.get(previous_board)
+        && let Some(board_arrangement_positions) = pointer_to_board.ptr.upgrade() {
+        let readable_board_arrangement_positions = board_arrangement_positions.read().unwrap();
+        let board_state = readable_board_arrangement_positions.get(pointer_to_board.index).read().unwrap();
+        board_state.repeated_moves.write().unwrap().insert(*mv);
+    }
+}
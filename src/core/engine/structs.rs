@@ -1,31 +1,32 @@
-use std::time::Instant;
+use std::sync::Arc;
 
-use crate::core::{chess::{board::Board, board_state::Evaluation}, structs::{cash::Cash, queue::DistributedQueue, weighted_queue::DistributedWeightedQueue}};
+use crate::core::{chess::{board::Board, board_state::Evaluation, chess_move::Move}, engine::repetition::SearchPath, structs::{cash::Cash, queue::DistributedQueue, weighted_queue::DistributedWeightedQueue}};
 
 pub type PositionsToEvaluate = DistributedWeightedQueue<PositionToEvaluate>;
-pub type PositionToReevaluate = (Board, (Board, TimestampedEvaluation));
+pub type PositionToReevaluate = (Board, (Move, TimestampedEvaluation));
 pub type TimestampedEvaluation = (Evaluation, std::time::Instant);
 pub type PositionsToReevaluate = DistributedQueue<PositionToReevaluate>;
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct PositionToEvaluate {
-    pub value: (Option<Board>, Board)
+    pub value: (Option<(Board, Move)>, Board),
+    pub path: Option<Arc<SearchPath>>,
 }
 
 impl Cash for PositionToEvaluate {
-    fn cash(self: &Self) -> u64 {
+    fn cash(&self) -> u64 {
         self.value.1.cash()
     }
 }
 
 impl Cash for (usize, Board) {
-    fn cash(self: &Self) -> u64 {
+    fn cash(&self) -> u64 {
         self.1.cash()
     }
 }
 
 impl Cash for PositionToReevaluate {
-    fn cash(self: &Self) -> u64 {
+    fn cash(&self) -> u64 {
         self.0.cash()
     }
 }
\ No newline at end of file
@@ -0,0 +1,169 @@
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+
+use crate::core::chess::{board_state::{Evaluation, PositionResult}, chess_move::Move};
+
+pub const DEFAULT_SIZE_MB: usize = 16;
+pub const MAX_SIZE_MB: usize = 65536;
+// Entries are grouped in clusters of a cache line, any of which can hold a position.
+const CLUSTER_SIZE: usize = 4;
+const ENTRY_BYTES: usize = 2 * size_of::<u64>();
+
+/// How the stored evaluation relates to the position's: equal to it, or a bound it was found to be beyond.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Bound {
+    Exact,
+    /// The position is at least this good, as the search stopped at the first move reaching beta.
+    Lower,
+    /// The position is at most this good, as no move reached alpha.
+    Upper,
+}
+
+/// What the search found out about a position.
+#[derive(Clone, Copy, PartialEq)]
+pub struct TableEntry {
+    pub depth: usize,
+    pub bound: Bound,
+    pub evaluation: Evaluation,
+    pub best_move: Option<Move>,
+}
+
+// An entry packed into 64 bits, from the lowest: the best move (20 bits), the result (2), the score (16), the depth (8),
+// the bound (2) and the age (8). The highest bit tells used entries from empty ones.
+const USED: u64 = 1 << 63;
+
+fn pack(entry: &TableEntry, age: u8) -> u64 {
+    let best_move = match entry.best_move {
+        Some(mv) => 1 | (mv.from as u64) << 1 | (mv.to as u64) << 7 | (mv.promotion as u64 >> 3) << 13 | (mv.flags as u64 & 0b1111) << 16,
+        None => 0,
+    };
+    let result = match entry.evaluation.result {
+        PositionResult::Win => 0,
+        PositionResult::Scored => 1,
+        PositionResult::Draw => 2,
+        PositionResult::Loss => 3,
+    };
+    let score = entry.evaluation.score.clamp(i16::MIN as i32, i16::MAX as i32) as i16 as u16 as u64;
+    let bound = match entry.bound {
+        Bound::Exact => 0,
+        Bound::Lower => 1,
+        Bound::Upper => 2,
+    };
+    USED | best_move | result << 20 | score << 22 | (entry.depth.min(u8::MAX as usize) as u64) << 38 | bound << 46 | (age as u64) << 48
+}
+
+fn unpack(data: u64) -> TableEntry {
+    let best_move = if data & 1 != 0 {
+        Some(Move {
+            from: (data >> 1 & 0b111111) as u8,
+            to: (data >> 7 & 0b111111) as u8,
+            promotion: ((data >> 13 & 0b111) << 3) as u8,
+            flags: (data >> 16 & 0b1111) as u8,
+        })
+    } else {
+        None
+    };
+    let result = match data >> 20 & 0b11 {
+        0 => PositionResult::Win,
+        1 => PositionResult::Scored,
+        2 => PositionResult::Draw,
+        _ => PositionResult::Loss,
+    };
+    let bound = match data >> 46 & 0b11 {
+        0 => Bound::Exact,
+        1 => Bound::Lower,
+        _ => Bound::Upper,
+    };
+    TableEntry {
+        depth: (data >> 38 & 0xFF) as usize,
+        bound,
+        evaluation: Evaluation { result, score: (data >> 22 & 0xFFFF) as u16 as i16 as i32 },
+        best_move,
+    }
+}
+
+fn age_of(data: u64) -> u8 {
+    (data >> 48 & 0xFF) as u8
+}
+
+/// A fixed-size table of positions keyed by their Zobrist key, shared by every thread without locks. Each entry is a
+/// pair of words, the key xored with the data and the data, so that an entry torn by two threads writing it at once no
+/// longer matches its key and is ignored. The memory it takes is decided when it is created and never grows. It only
+/// serves the alpha-beta backend: the graph search's positions are not part of this budget.
+pub struct TranspositionTable {
+    entries: Box<[AtomicU64]>,
+    cluster_count: usize,
+    // Raised for every search, so that what earlier searches left is replaced first.
+    age: AtomicU8,
+}
+
+impl TranspositionTable {
+    pub fn new(size_mb: usize) -> Self {
+        let cluster_count = (size_mb.clamp(1, MAX_SIZE_MB) * 1024 * 1024 / (ENTRY_BYTES * CLUSTER_SIZE)).max(1);
+        TranspositionTable {
+            entries: (0..cluster_count * CLUSTER_SIZE * 2).map(|_| AtomicU64::new(0)).collect(),
+            cluster_count,
+            age: AtomicU8::new(0),
+        }
+    }
+
+    pub fn size_mb(&self) -> usize {
+        self.cluster_count * CLUSTER_SIZE * ENTRY_BYTES / (1024 * 1024)
+    }
+
+    /// Called when a search starts.
+    pub fn new_search(&self) {
+        self.age.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn clear(&self) {
+        for word in self.entries.iter() {
+            word.store(0, Ordering::Relaxed);
+        }
+    }
+
+    fn cluster(&self, key: u64) -> usize {
+        // The high bits pick the cluster, leaving the low ones to the shards of the search graph.
+        ((key as u128 * self.cluster_count as u128) >> 64) as usize * CLUSTER_SIZE * 2
+    }
+
+    fn load(&self, index: usize) -> (u64, u64) {
+        let data = self.entries[index + 1].load(Ordering::Relaxed);
+        (self.entries[index].load(Ordering::Relaxed) ^ data, data)
+    }
+
+    pub fn probe(&self, key: u64) -> Option<TableEntry> {
+        let cluster = self.cluster(key);
+        (0..CLUSTER_SIZE).map(|slot| self.load(cluster + slot * 2))
+            .find(|(entry_key, data)| *entry_key == key && data & USED != 0)
+            .map(|(_, data)| unpack(data))
+    }
+
+    /// Stores `entry` for the position with `key`. It takes the position's own slot if it has one, keeping the best
+    /// move found earlier when it brings none. Otherwise it replaces the entry of the oldest search, and among those
+    /// the shallowest.
+    pub fn store(&self, key: u64, mut entry: TableEntry) {
+        let age = self.age.load(Ordering::Relaxed);
+        let cluster = self.cluster(key);
+        let mut replaced = cluster;
+        let mut replaced_worth = i32::MAX;
+        for slot in 0..CLUSTER_SIZE {
+            let index = cluster + slot * 2;
+            let (entry_key, data) = self.load(index);
+            if entry_key == key || data & USED == 0 {
+                if entry_key == key && entry.best_move.is_none() {
+                    entry.best_move = unpack(data).best_move;
+                }
+                replaced = index;
+                break;
+            }
+            let worth = unpack(data).depth as i32 - 8 * age.wrapping_sub(age_of(data)) as i32;
+            if worth < replaced_worth {
+                replaced = index;
+                replaced_worth = worth;
+            }
+        }
+        let data = pack(&entry, age);
+        self.entries[replaced].store(key ^ data, Ordering::Relaxed);
+        self.entries[replaced + 1].store(data, Ordering::Relaxed);
+    }
+}
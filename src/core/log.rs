@@ -6,9 +6,10 @@ pub static ENABLE_LOG: RwLock<bool> = RwLock::new(true);
 #[macro_export]
 macro_rules! log {
     ($($arg:tt)*) => {
-        let enable_logging: bool = *crate::core::log::ENABLE_LOG.read().unwrap();
-        if enable_logging {
-            let path = crate::core::log::FILENAME.read().unwrap().to_string();
+        let enable_logging: bool = *$crate::core::log::ENABLE_LOG.read().unwrap();
+        // Programs embedding the engine log nothing unless they name a file.
+        let path = $crate::core::log::FILENAME.read().unwrap().to_string();
+        if enable_logging && !path.is_empty() {
             std::fs::create_dir_all(std::path::Path::new(&path).parent().unwrap().clone()).unwrap();
             let mut file = std::fs::OpenOptions::new()
             .write(true)
@@ -0,0 +1,184 @@
+use std::{fmt::Display, sync::atomic::{AtomicBool, Ordering}, time::Duration};
+
+use crate::{core::{chess::{board_state::Evaluation, chess_move::Move}, engine::handle::Engine, time_manager::TimeManager}, log};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+// Every node of the graph is kept in memory, so there is no point in asking for more than this.
+pub const MAX_DEPTH: usize = 64;
+
+/// How the engine searches. The graph search keeps every position it reaches and shares them between its threads,
+/// while alpha-beta searches depth first on a single thread in constant memory.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SearchBackend {
+    #[default]
+    Graph,
+    AlphaBeta,
+}
+
+impl SearchBackend {
+    pub const ALL: [SearchBackend; 2] = [SearchBackend::Graph, SearchBackend::AlphaBeta];
+
+    pub fn from_name(name: &str) -> Option<SearchBackend> {
+        SearchBackend::ALL.into_iter().find(|backend| backend.to_string().eq_ignore_ascii_case(name))
+    }
+}
+
+impl Display for SearchBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            SearchBackend::Graph => "Graph",
+            SearchBackend::AlphaBeta => "AlphaBeta",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Parts of the search that can be switched on and off, to measure what each of them is worth.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SearchOptions {
+    /// Whether quiescence also tries the moves that give check.
+    pub quiescence_checks: bool,
+    /// Whether alpha-beta cuts off a position where the side to move stays above beta even after passing its turn.
+    pub null_move_pruning: bool,
+    /// Whether alpha-beta searches the quiet moves ordered late less deep, and only fully if they turn out better.
+    pub late_move_reductions: bool,
+    /// The reduction of the `n`th move of a search `d` plies deep is `lmr_base + ln(d) * ln(n) / lmr_divisor` plies,
+    /// with both in hundredths.
+    pub lmr_base: u32,
+    pub lmr_divisor: u32,
+    /// Whether alpha-beta skips the quiet moves of the last plies when the score is too far below alpha for them to
+    /// bring it back.
+    pub futility_pruning: bool,
+    /// Whether alpha-beta only searches the captures of the last plies when the score is far below alpha.
+    pub razoring: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            quiescence_checks: false,
+            null_move_pruning: true,
+            late_move_reductions: true,
+            lmr_base: 75,
+            lmr_divisor: 225,
+            futility_pruning: true,
+            razoring: true,
+        }
+    }
+}
+
+/// When a search run by a protocol front end has to stop. Clock times are what is left for each side.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct SearchLimits {
+    pub depth: Option<usize>,
+    pub movetime: Option<Duration>,
+    pub wtime: Option<Duration>,
+    pub btime: Option<Duration>,
+    pub winc: Option<Duration>,
+    pub binc: Option<Duration>,
+    pub movestogo: Option<usize>,
+    pub infinite: bool,
+}
+
+/// What the search has found after completing a depth. The evaluation is from the side to move.
+#[derive(Clone)]
+pub struct SearchInfo {
+    pub depth: usize,
+    pub evaluation: Evaluation,
+    pub nodes: usize,
+    pub elapsed: Duration,
+    pub principal_variation: Vec<Move>,
+}
+
+impl SearchInfo {
+    pub fn nodes_per_second(&self) -> u64 {
+        (self.nodes as f64 / self.elapsed.as_secs_f64().max(0.001)) as u64
+    }
+}
+
+/// Keeps the evaluation threads from searching below the current position until the next search.
+pub fn pause_search(engine: &Engine) {
+    *engine.current_depth.write().unwrap() = *engine.root_depth.read().unwrap();
+}
+
+fn is_depth_searched(engine: &Engine, depth: usize) -> bool {
+    let is_queue_searched = || engine.positions_to_evaluate.lengths().iter().all(|(weight, length)| *weight > depth || *length == 0);
+    if !is_queue_searched() {
+        return false;
+    }
+    // The evaluation threads hold the run lock while they evaluate, so their children are queued once it is released.
+    let run_lock_lock = engine.run_lock.write().unwrap();
+    let is_searched = is_queue_searched();
+    drop(run_lock_lock);
+    is_searched
+}
+
+/// Deepens the search of the current position one depth at a time until `limits` or `stop` end it, calling `report`
+/// after every completed depth. Returns the best move of the deepest completed depth, or None when there are no legal
+/// moves.
+pub fn search(engine: &Engine, limits: SearchLimits, stop: &AtomicBool, mut report: impl FnMut(&SearchInfo)) -> Option<Move> {
+    let position = engine.position();
+    let mut time_manager = TimeManager::new(&limits, position.side_to_move);
+    let root_depth = *engine.root_depth.read().unwrap();
+    let nodes_at_start = *engine.positions_evaluated_acount.read().unwrap();
+    let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+    log!("Search started: {:?}", limits);
+
+    // Whatever an earlier search left is the best there is until the first depth is completed.
+    let mut best_move = engine.next_best_move(&position.board).map(|next_best_move| next_best_move.mv).or_else(|| position.legal_moves().first().copied());
+    let mut depth = 1;
+    let mut completed_depth = 0;
+    // A depth only counts as searched after it has been seen finished twice, so that nothing is still on its way to the queue.
+    let mut finished_polls = 0;
+    *engine.current_depth.write().unwrap() = root_depth + depth;
+    engine.waiter.notify();
+    while best_move.is_some() {
+        std::thread::sleep(POLL_INTERVAL);
+        if stop.load(Ordering::Relaxed) || time_manager.is_hard_limit_reached() {
+            break;
+        }
+        if depth > completed_depth {
+            if engine.positions.get(&position.board).is_none() || !is_depth_searched(engine, root_depth + depth) {
+                finished_polls = 0;
+                // Threads that were about to wait when the depth was raised would otherwise sleep through it.
+                engine.waiter.notify();
+                continue;
+            }
+            finished_polls += 1;
+            if finished_polls < 2 {
+                continue;
+            }
+            finished_polls = 0;
+            engine.reevaluate();
+            completed_depth = depth;
+            if let Some(next_best_move) = engine.next_best_move(&position.board) {
+                best_move = Some(next_best_move.mv);
+                report(&SearchInfo {
+                    depth: completed_depth,
+                    evaluation: next_best_move.evaluation,
+                    nodes: *engine.positions_evaluated_acount.read().unwrap() - nodes_at_start,
+                    elapsed: time_manager.elapsed(),
+                    principal_variation: engine.principal_variation(completed_depth),
+                });
+            }
+            if !time_manager.should_deepen(best_move) && !limits.infinite {
+                break;
+            }
+        }
+        if completed_depth >= max_depth {
+            // An infinite search only returns once it is stopped, and whoever stops it wakes this thread up.
+            while limits.infinite && !stop.load(Ordering::Relaxed) {
+                std::thread::park();
+            }
+            break;
+        }
+        if depth == completed_depth {
+            depth += 1;
+            *engine.current_depth.write().unwrap() = root_depth + depth;
+            engine.waiter.notify();
+        }
+    }
+    pause_search(engine);
+    engine.reevaluate();
+    best_move
+}
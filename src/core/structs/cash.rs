@@ -1,5 +1,28 @@
-
+use std::hash::{BuildHasherDefault, Hasher};
 
 pub trait Cash {
-    fn cash(self: &Self) -> u64;
-}
\ No newline at end of file
+    fn cash(&self) -> u64;
+}
+
+/// Hashes values whose `Hash` writes nothing but their `Cash`, such as boards and their Zobrist keys, by passing it
+/// through instead of hashing it again.
+#[derive(Default)]
+pub struct CashHasher(u64);
+
+impl Hasher for CashHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = self.0.rotate_left(8) ^ *byte as u64;
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.0 ^= value;
+    }
+}
+
+pub type BuildCashHasher = BuildHasherDefault<CashHasher>;
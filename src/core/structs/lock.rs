@@ -1,4 +1,4 @@
-use std::sync::{Arc, Condvar, Mutex};
+use std::{sync::{Arc, Condvar, Mutex}, time::Duration};
 
 #[derive(Clone)]
 pub struct LockWaiter {
@@ -6,6 +6,12 @@ pub struct LockWaiter {
     waiter: Arc<Condvar>
 }
 
+impl Default for LockWaiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl LockWaiter {
     pub fn new() -> LockWaiter{
         LockWaiter {
@@ -14,11 +20,15 @@ impl LockWaiter {
         }
     }
 
-    pub fn wait(self: &Self) {
+    pub fn wait(&self) {
         self.waiter.wait(self.lock.lock().unwrap()).unwrap();
     }
 
-    pub fn notify(self: &Self) {
+    pub fn wait_timeout(&self, duration: Duration) {
+        let _ = self.waiter.wait_timeout(self.lock.lock().unwrap(), duration).unwrap();
+    }
+
+    pub fn notify(&self) {
         self.waiter.notify_all();
     }
 }
\ No newline at end of file
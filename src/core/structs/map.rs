@@ -1,6 +1,6 @@
 use std::{collections::{HashMap, HashSet}, hash::{DefaultHasher, Hash, Hasher, RandomState}, sync::{Arc, RwLock, Weak}};
 
-use crate::{core::{chess::{board::{Board, BoardArrangement}, board_state::BoardState}, structs::cash::Cash}, log};
+use crate::{core::{chess::{board::{Board, BoardArrangement}, board_state::BoardState}, structs::cash::{BuildCashHasher, Cash}}, log};
 
 pub struct PointerToBoard {
     pub ptr: Weak<RwLock<BoardArrangementPositions>>,
@@ -42,7 +42,13 @@ impl GroupedPositions {
         self.map[index].clone().unwrap().get(board)
     }
 
-    pub fn len(self: &Self) -> String {
+    pub fn clear(&self) {
+        for i in 0..self.length {
+            self.map[i].clone().unwrap().map.write().unwrap().clear();
+        }
+    }
+
+    pub fn len(&self) -> String {
         let mut lens = vec![];
         for i in 0..self.length {
             let position = self.map[i].clone().unwrap();
@@ -66,15 +72,21 @@ pub const PAGE_SIZE: usize = 4096 * 1024;
 pub const PAGE_BOARD_COUNT: usize = 4096;
 
 pub struct BoardArrangementPositions {
-    pub map: HashMap<Board, usize>,
+    pub map: HashMap<Board, usize, BuildCashHasher>,
     pub positions: [Option<Box<Vec<RwLock<BoardState>>>>; 128],
     pub size: usize,
 }
 
+impl Default for BoardArrangementPositions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BoardArrangementPositions {
     pub fn new() -> Self {
         BoardArrangementPositions {
-            map: HashMap::new(),
+            map: HashMap::default(),
             positions: std::array::from_fn(|_| { None }),
             size: 0,
         }
@@ -98,6 +110,12 @@ pub enum Presence<T> {
     Absent{value: T},
 }
 
+impl Default for Positions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Positions {
 
     pub fn new() -> Self {
@@ -104,6 +104,12 @@ fn lock_head<'a, T>(m: &'a Arc<Mutex<Option<QueueNode<T>>>>) -> MutexGuard<'a, O
     m.lock().unwrap()
 }
 
+impl<T: Clone> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Clone> Queue<T> {
     pub fn new() -> Self {
         Queue {
@@ -8,6 +8,12 @@ pub struct ReevaluationQueue {
     board_depth_map: Arc<RwLock<HashMap<Board, usize>>>,
 }
 
+impl Default for ReevaluationQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ReevaluationQueue {
 
     pub fn new() -> Self {
@@ -121,4 +127,8 @@ impl ReevaluationQueue {
         let readable_queues = self.depth_queues.read().unwrap();
         readable_queues.values().map(|queue| queue.lock().unwrap().len()).sum()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
\ No newline at end of file
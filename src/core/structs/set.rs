@@ -7,6 +7,12 @@ pub struct Set<T: Ord> {
     pub length: Arc<RwLock<usize>>,
 }
 
+impl<T: Ord> Default for Set<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Ord> Set<T> {
     pub fn new() -> Self {
         Set {
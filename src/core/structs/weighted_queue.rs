@@ -1,7 +1,9 @@
-use std::{collections::BTreeMap, sync::{Arc, Condvar, Mutex, RwLock}};
+use std::{collections::BTreeMap, sync::{Arc, Condvar, Mutex, RwLock}, time::Duration};
 
 use crate::{core::structs::{cash::Cash, lock::LockWaiter, queue::Queue, threaded_queue::ThreadedQueue}, log};
 
+const EMPTY_QUEUE_WAIT: Duration = Duration::from_millis(10);
+
 #[derive(Clone)]
 pub struct WeightedQueue<T> {
     pub thread_count: usize,
@@ -21,23 +23,22 @@ impl<T: Clone> WeightedQueue<T> {
     }
 
     pub fn queue(&self, value: Vec<T>, weight: usize) {
-        let queues = {
-            let readable_queues = self.queues.read().unwrap();
-            match readable_queues.get(&weight) {
-                Some(queue) => {
-                    queue.clone()
-                }
-                None => {
-                    drop(readable_queues);
-                    let mut writable_queues = self.queues.write().unwrap();
-                    let queue = ThreadedQueue::new(self.thread_count);
-                    writable_queues.insert(weight, queue.clone());
-                    self.waiter.notify();
-                    queue
-                }
+        // The values are added while the map is locked, since an empty queue can be removed from it as soon as it is
+        // unlocked and whatever is added to it afterwards would be lost.
+        let readable_queues = self.queues.read().unwrap();
+        match readable_queues.get(&weight) {
+            Some(queue) => {
+                queue.queue(value);
             }
-        };
-        queues.queue(value);
+            None => {
+                drop(readable_queues);
+                let mut writable_queues = self.queues.write().unwrap();
+                let queue = writable_queues.entry(weight).or_insert_with(|| ThreadedQueue::new(self.thread_count));
+                queue.queue(value);
+                drop(writable_queues);
+                self.waiter.notify();
+            }
+        }
     }
 
     pub fn dequeue_optional(&self) -> Option<(usize, Vec<T>)> {
@@ -72,7 +73,12 @@ impl<T: Clone> WeightedQueue<T> {
                         }
                     }
                 },
-                None => {},
+                None => {
+                    // Nothing is queued, so give up the depth lock instead of spinning on it and let the caller retry.
+                    drop(max);
+                    self.waiter.wait_timeout(EMPTY_QUEUE_WAIT);
+                    return None;
+                },
             }
         }
     }
@@ -82,6 +88,14 @@ impl<T: Clone> WeightedQueue<T> {
         readable_queues.values().map(|queue| *queue.length.read().unwrap()).sum()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.queues.write().unwrap().clear();
+    }
+
     pub fn lengths(&self) -> BTreeMap<usize, usize> {
         let readable_queues = self.queues.read().unwrap();
         readable_queues.iter().map(|(key, queue)| (*key, *queue.length.read().unwrap())).collect()
@@ -145,6 +159,16 @@ impl<T: Clone + Cash> DistributedWeightedQueue<T> {
         self.queues.iter().map(|queue| queue.len()).sum()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.queues.iter().all(|queue| queue.is_empty())
+    }
+
+    pub fn clear(&self) {
+        for queue in self.queues.iter() {
+            queue.clear();
+        }
+    }
+
     pub fn lengths(&self) -> BTreeMap<usize, usize> {
         let mut lengths: BTreeMap<usize, usize> = BTreeMap::new();
         for queue in self.queues.iter() {
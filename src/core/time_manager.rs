@@ -0,0 +1,102 @@
+use std::time::{Duration, Instant};
+
+use crate::core::{chess::{chess_move::Move, position::Color}, search::SearchLimits};
+
+// Taken off the clock for every move to cover the time it takes the GUI to receive it.
+const MOVE_OVERHEAD: Duration = Duration::from_millis(50);
+// How many moves are assumed to be left when the time control does not say.
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+// How far past its share of the clock a depth that is already under way may run.
+const HARD_LIMIT_FACTOR: u32 = 4;
+// A depth takes longer than all the ones before it together, so none is started past this part of the soft limit.
+const NEXT_DEPTH_FRACTION: f64 = 0.5;
+
+/// Decides how long a search may think. The soft limit is the share of the clock a move should take, and is cut down
+/// while the best move stays the same from one depth to the next. The hard limit is where a depth in progress is
+/// abandoned, leaving the best move of the last completed depth.
+pub struct TimeManager {
+    start_time: Instant,
+    soft_limit: Option<Duration>,
+    hard_limit: Option<Duration>,
+    // Set when the whole time given has to be used, as for `movetime`.
+    fixed: bool,
+    best_move: Option<Move>,
+    stable_depths: usize,
+}
+
+impl TimeManager {
+    pub fn new(limits: &SearchLimits, side: Color) -> Self {
+        let start_time = Instant::now();
+        let (time, increment) = match side {
+            Color::White => (limits.wtime, limits.winc.unwrap_or_default()),
+            Color::Black => (limits.btime, limits.binc.unwrap_or_default()),
+        };
+        let (soft_limit, hard_limit, fixed) = if limits.infinite {
+            (None, None, false)
+        } else if let Some(movetime) = limits.movetime {
+            let movetime = movetime.saturating_sub(MOVE_OVERHEAD);
+            (Some(movetime), Some(movetime), true)
+        } else if let Some(time) = time {
+            let available = time.saturating_sub(MOVE_OVERHEAD);
+            let moves_to_go = limits.movestogo.map_or(DEFAULT_MOVES_TO_GO, |moves_to_go| moves_to_go.max(1) as u32);
+            let soft_limit = (time / moves_to_go + increment * 3 / 4).min(available / 2);
+            let hard_limit = (soft_limit * HARD_LIMIT_FACTOR).min(available / 2).max(soft_limit);
+            (Some(soft_limit), Some(hard_limit), false)
+        } else {
+            (None, None, false)
+        };
+        TimeManager { start_time, soft_limit, hard_limit, fixed, best_move: None, stable_depths: 0 }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start_time.elapsed()
+    }
+
+    pub fn soft_limit(&self) -> Option<Duration> {
+        self.soft_limit
+    }
+
+    pub fn hard_limit(&self) -> Option<Duration> {
+        self.hard_limit
+    }
+
+    /// When the search has to stop, even in the middle of a depth.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.hard_limit.map(|hard_limit| self.start_time + hard_limit)
+    }
+
+    pub fn is_hard_limit_reached(&self) -> bool {
+        self.hard_limit.is_some_and(|hard_limit| self.elapsed() >= hard_limit)
+    }
+
+    /// Records the best move of a depth that was just completed and tells whether there is time for the next one.
+    pub fn should_deepen(&mut self, best_move: Option<Move>) -> bool {
+        if best_move == self.best_move {
+            self.stable_depths += 1;
+        } else {
+            self.best_move = best_move;
+            self.stable_depths = 0;
+        }
+        match self.next_depth_limit() {
+            Some(next_depth_limit) => self.elapsed() < next_depth_limit,
+            None => !self.is_hard_limit_reached(),
+        }
+    }
+
+    /// The time past which no new depth is started, given how long the best move has stayed the same. None when the
+    /// search runs until the hard limit, if it has one.
+    pub fn next_depth_limit(&self) -> Option<Duration> {
+        let soft_limit = match self.soft_limit {
+            Some(soft_limit) if !self.fixed => soft_limit,
+            _ => return None,
+        };
+        // A best move that keeps changing deserves a little more time, one that has settled a lot less.
+        let factor = match self.stable_depths {
+            0 => 1.2,
+            1 => 1.0,
+            2 => 0.8,
+            _ => 0.6,
+        };
+        Some(soft_limit.mul_f64(factor * NEXT_DEPTH_FRACTION))
+    }
+}
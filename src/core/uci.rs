@@ -0,0 +1,202 @@
+use std::{sync::{Arc, atomic::{AtomicBool, Ordering}}, time::Duration};
+
+use crate::{core::{chess::{board_state::{Evaluation, PositionResult}, position::Position}, engine::{handle::Engine, transposition_table::MAX_SIZE_MB}, search::{SearchBackend, SearchInfo, SearchLimits, pause_search}}, log};
+
+const AUTHOR: &str = "the chess-engine authors";
+
+/// Reads the arguments of a `go` command. Arguments it does not know are skipped.
+pub fn parse_go(arguments: &[&str]) -> SearchLimits {
+    let mut limits = SearchLimits::default();
+    let mut i = 0;
+    while i < arguments.len() {
+        let value = arguments.get(i + 1).and_then(|value| value.parse::<u64>().ok());
+        let duration = value.map(Duration::from_millis);
+        match arguments[i] {
+            "depth" => limits.depth = value.map(|depth| depth as usize),
+            "movetime" => limits.movetime = duration,
+            "wtime" => limits.wtime = duration,
+            "btime" => limits.btime = duration,
+            "winc" => limits.winc = duration,
+            "binc" => limits.binc = duration,
+            "movestogo" => limits.movestogo = value.map(|moves| moves as usize),
+            "infinite" => limits.infinite = true,
+            _ => {
+                i += 1;
+                continue;
+            },
+        }
+        i += if arguments[i] == "infinite" { 1 } else { 2 };
+    }
+    limits
+}
+
+/// `score cp` in centipawns from the side to move, or `score mate` in moves.
+pub fn score_string(evaluation: &Evaluation) -> String {
+    match evaluation.result {
+        PositionResult::Win => format!("mate {}", (evaluation.score + 1) / 2),
+        // Being mated already is `mate 0`, not `mate -0`.
+        PositionResult::Loss => format!("mate {}", -((evaluation.score + 1) / 2)),
+        PositionResult::Draw => String::from("cp 0"),
+        PositionResult::Scored => format!("cp {}", evaluation.score * 100),
+    }
+}
+
+/// Reads a `position` command: `startpos` or `fen <fen>`, optionally followed by `moves` and the moves played.
+pub fn parse_position(arguments: &[&str]) -> Result<Position, String> {
+    let moves_index = arguments.iter().position(|argument| *argument == "moves").unwrap_or(arguments.len());
+    let mut position = match arguments.first() {
+        Some(&"startpos") => Position::initial(),
+        Some(&"fen") => Position::from_fen(&arguments[1..moves_index].join(" ")).map_err(|error| error.to_string())?,
+        _ => return Err(String::from("expected startpos or fen")),
+    };
+    for notation in arguments.iter().skip(moves_index + 1) {
+        let mv = position.parse_uci(notation).map_err(|error| error.to_string())?;
+        position.play(mv);
+    }
+    Ok(position)
+}
+
+fn print_info(position: &Position, info: &SearchInfo, show_san_pv: bool) {
+    println!(
+        "info depth {} score {} nodes {} nps {} time {} pv {}",
+        info.depth,
+        score_string(&info.evaluation),
+        info.nodes,
+        info.nodes_per_second(),
+        info.elapsed.as_millis(),
+        position.line_to_uci(&info.principal_variation).join(" "),
+    );
+    // GUIs need the line in coordinates, people read it better in SAN.
+    if show_san_pv {
+        println!("info string pv {}", position.line_to_numbered_san(&info.principal_variation));
+    }
+}
+
+/// Speaks the Universal Chess Interface on `lines` until `quit` or the end of the input. The engine threads keep
+/// running afterwards, for the caller to reuse the engine or exit.
+pub fn uci(engine: Engine, lines: impl Iterator<Item = String>) {
+    // Nothing is searched until the GUI sends `go`.
+    pause_search(&engine);
+    engine.start_threads();
+    let show_san_pv = Arc::new(AtomicBool::new(false));
+    {
+        let show_san_pv = show_san_pv.clone();
+        engine.on_progress(move |engine, info| print_info(&engine.position(), info, show_san_pv.load(Ordering::Relaxed)));
+    }
+    engine.on_best_move(|engine, best_move| {
+        let position = engine.position();
+        // The move after the best one is the reply the GUI may ponder on.
+        let pv = position.line_to_uci(&engine.principal_variation(2));
+        match (best_move.map(|best_move| position.to_uci(best_move)), pv.get(1)) {
+            (Some(best_move), Some(ponder)) if pv[0] == best_move => println!("bestmove {} ponder {}", best_move, ponder),
+            (Some(best_move), _) => println!("bestmove {}", best_move),
+            (None, _) => println!("bestmove 0000"),
+        }
+    });
+    // The limits of the search a `go ponder` put off until `ponderhit` says that the expected reply was played.
+    let mut ponder_limits = None;
+    for line in lines {
+        log!("UCI command: {}", line);
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let (command, arguments) = match tokens.split_first() {
+            Some((command, arguments)) => (*command, arguments),
+            None => continue,
+        };
+        match command {
+            "uci" => {
+                println!("id name {} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+                println!("id author {}", AUTHOR);
+                println!("option name Log type check default {}", *crate::core::log::ENABLE_LOG.read().unwrap());
+                let backends: Vec<String> = SearchBackend::ALL.iter().map(|backend| format!("var {}", backend)).collect();
+                println!("option name Backend type combo default {} {}", engine.backend(), backends.join(" "));
+                let options = engine.options();
+                println!("option name QuiescenceChecks type check default {}", options.quiescence_checks);
+                println!("option name NullMovePruning type check default {}", options.null_move_pruning);
+                println!("option name LateMoveReductions type check default {}", options.late_move_reductions);
+                println!("option name LmrBase type spin default {} min 0 max 1000", options.lmr_base);
+                println!("option name LmrDivisor type spin default {} min 1 max 1000", options.lmr_divisor);
+                println!("option name FutilityPruning type check default {}", options.futility_pruning);
+                println!("option name Razoring type check default {}", options.razoring);
+                println!("option name Ponder type check default false");
+                println!("option name ShowSanPv type check default {}", show_san_pv.load(Ordering::Relaxed));
+                println!("option name Hash type spin default {} min 1 max {}", engine.hash_size(), MAX_SIZE_MB);
+                println!("uciok");
+            },
+            "isready" => println!("readyok"),
+            "ucinewgame" => {
+                engine.stop();
+                engine.set_position(Position::initial());
+                engine.clear_hash();
+                pause_search(&engine);
+            },
+            "position" => {
+                engine.stop();
+                match parse_position(arguments) {
+                    Ok(position) => {
+                        engine.continue_to(position);
+                        pause_search(&engine);
+                    },
+                    Err(error) => println!("info string invalid position: {}", error),
+                }
+            },
+            "go" => {
+                if engine.is_searching() {
+                    engine.stop();
+                }
+                let limits = parse_go(arguments);
+                // Pondering goes on for as long as the opponent thinks, so its clock only counts from `ponderhit`.
+                if arguments.contains(&"ponder") {
+                    ponder_limits = Some(limits);
+                    engine.start(SearchLimits { infinite: true, ..limits });
+                } else {
+                    ponder_limits = None;
+                    engine.start(limits);
+                }
+            },
+            // The pondering search is replaced by one with the limits it was given, without a best move for the GUI.
+            "ponderhit" => match ponder_limits.take() {
+                Some(limits) => engine.start(limits),
+                None => println!("info string not pondering"),
+            },
+            "stop" => {
+                ponder_limits = None;
+                engine.stop();
+            },
+            "setoption" => {
+                let value_index = arguments.iter().position(|argument| *argument == "value").unwrap_or(arguments.len());
+                let name = arguments[1.min(value_index)..value_index].join(" ");
+                let value = arguments.get(value_index + 1..).map(|value| value.join(" ")).unwrap_or_default();
+                match name.as_str() {
+                    "Log" => *crate::core::log::ENABLE_LOG.write().unwrap() = value == "true",
+                    "Backend" => match SearchBackend::from_name(&value) {
+                        Some(backend) => engine.set_backend(backend),
+                        None => println!("info string unknown backend: {}", value),
+                    },
+                    "QuiescenceChecks" => engine.update_options(|options| options.quiescence_checks = value == "true"),
+                    "NullMovePruning" => engine.update_options(|options| options.null_move_pruning = value == "true"),
+                    "LateMoveReductions" => engine.update_options(|options| options.late_move_reductions = value == "true"),
+                    "LmrBase" | "LmrDivisor" => match value.parse::<u32>() {
+                        Ok(value) if name == "LmrBase" => engine.update_options(|options| options.lmr_base = value),
+                        Ok(value) => engine.update_options(|options| options.lmr_divisor = value.max(1)),
+                        Err(_) => println!("info string invalid value for {}: {}", name, value),
+                    },
+                    "FutilityPruning" => engine.update_options(|options| options.futility_pruning = value == "true"),
+                    "Razoring" => engine.update_options(|options| options.razoring = value == "true"),
+                    // Whether the GUI sends `go ponder` is up to it, so there is nothing to set.
+                    "Ponder" => {},
+                    "ShowSanPv" => show_san_pv.store(value == "true", Ordering::Relaxed),
+                    "Hash" => match value.parse::<usize>() {
+                        Ok(size_mb) => engine.set_hash_size(size_mb),
+                        Err(_) => println!("info string invalid hash size: {}", value),
+                    },
+                    _ => println!("info string unknown option: {}", name),
+                }
+            },
+            "quit" => {
+                engine.stop();
+                break;
+            },
+            _ => println!("info string unknown command: {}", command),
+        }
+    }
+}
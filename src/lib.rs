@@ -0,0 +1,18 @@
+//! The engine without its terminal UI. Build an [`Engine`], give it a position and start searching it:
+//!
+//! ```no_run
+//! use chess::{Engine, Position, SearchLimits};
+//!
+//! let engine = Engine::new(4);
+//! engine.on_best_move(|engine, best_move| {
+//!     if let Some(best_move) = best_move {
+//!         println!("{}", engine.position().to_san(best_move));
+//!     }
+//! });
+//! engine.set_position(Position::initial());
+//! engine.start(SearchLimits { depth: Some(4), ..SearchLimits::default() });
+//! ```
+
+pub mod core;
+
+pub use crate::core::{chess::{board_state::{Evaluation, PositionResult}, chess_move::Move, position::{Color, Position}}, engine::handle::Engine, search::{SearchInfo, SearchLimits}};
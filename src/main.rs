@@ -1,13 +1,20 @@
-mod core;
+#[cfg(feature = "tui")]
+mod app;
 
+use std::{io::IsTerminal, time::Instant};
+
+#[cfg(target_os = "macos")]
 use mac_notification_sys::*;
 
-use crate::core::{app::App, chess::board::{Board, BoardArrangement}, engine::reevaluation_engine::{move_board, move_board_arrangement}, log::FILENAME};
+use chess::{core::{chess::{board::{Board, BoardArrangement}, position::Position}, engine::{handle::Engine, reevaluation_engine::{move_board, move_board_arrangement}}, log::FILENAME, uci::uci, cecp::cecp}, log};
 
 fn main() {
 
-    let bundle = get_bundle_identifier_or_default("firefox");
-    set_application(&bundle).unwrap();
+    #[cfg(target_os = "macos")]
+    {
+        let bundle = get_bundle_identifier_or_default("firefox");
+        set_application(&bundle).unwrap();
+    }
     unsafe {
         let f = format!("logs/{}.log", chrono::Local::now().format("%Y-%m-%d_%H-%M-%S").to_string());
         let mut file_name = FILENAME.write().unwrap();
@@ -16,24 +23,14 @@ fn main() {
         match std::env::var("LOG") {
             Ok(value) => {
                 if value == "false" {
-                    let mut enable_log = crate::core::log::ENABLE_LOG.write().unwrap();
+                    let mut enable_log = chess::core::log::ENABLE_LOG.write().unwrap();
                     *enable_log = false;
                 }
             },
             Err(_) => {},
         };
 
-        match std::env::var("TIMED") {
-            Ok(value) => {
-                if value == "true" {
-                    let mut timed = crate::core::engine::evaluation_engine::TIMED.write().unwrap();
-                    *timed = true;
-                }
-            },
-            Err(_) => {},
-        };
-
-        let board: Board = serde_json::from_str("{\"pieces\":[144,0,160,176,168,160,152,144,136,136,136,136,136,136,136,136,0,0,152,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,200,200,0,0,0,0,0,0,0,0,0,0,0,200,200,200,0,0,200,200,200,208,216,224,240,232,224,216,208]}").unwrap();
+        let board = Board::from_fen("rnbqkb1r/pppppppp/5n2/8/3PP3/8/PPP2PPP/RNBQKBNR b KQkq - 0 2").unwrap();
         *move_board.write().unwrap() = board;
     }
 
@@ -43,8 +40,80 @@ fn main() {
     log!("Hello, world!");
     let thread_count = std::thread::available_parallelism().unwrap().get();
     // let thread_count = 6;
-    let mut app = App::new(thread_count-1);
+    let engine = Engine::new(thread_count.saturating_sub(1).max(1));
 
-    let _ = app.run();
-    ratatui::restore();
+    // The starting position can be given with `--fen "<fen>"` or the FEN environment variable.
+    let fen = std::env::args().skip_while(|arg| arg != "--fen").nth(1).or(std::env::var("FEN").ok());
+    if let Some(fen) = fen {
+        match Position::from_fen(&fen) {
+            Ok(position) => engine.set_position(position),
+            Err(error) => {
+                eprintln!("Invalid FEN: {}", error);
+                return;
+            }
+        }
+    }
+
+    // `perft <depth>` counts the positions reached after each move of the starting position, to check the move
+    // generator against another engine's.
+    let argument = |name: &str| std::env::args().skip_while(|arg| arg != name).nth(1);
+    if std::env::args().any(|arg| arg == "perft") {
+        let depth = match argument("perft").and_then(|depth| depth.parse::<usize>().ok()).filter(|depth| *depth > 0) {
+            Some(depth) => depth,
+            None => {
+                eprintln!("Usage: chess perft <depth> [--fen \"<fen>\"]");
+                return;
+            }
+        };
+        let position = engine.position();
+        let start_time = Instant::now();
+        let mut divide: Vec<(String, usize)> = position.board.divide(depth).into_iter().map(|(mv, nodes)| (position.to_uci(mv), nodes)).collect();
+        divide.sort();
+        for (mv, nodes) in divide.iter() {
+            println!("{}: {}", mv, nodes);
+        }
+        println!();
+        println!("Nodes searched: {}", divide.iter().map(|(_, nodes)| nodes).sum::<usize>());
+        println!("Time: {}ms", start_time.elapsed().as_millis());
+        return;
+    }
+
+    // GUIs talk to the engine through a pipe, while the TUI needs a terminal. Which protocol they speak is given as an
+    // argument, or else told by their first command: XBoard opens with `xboard`, anything else is taken to be UCI.
+    // Built without the TUI, the engine always speaks one of them.
+    let protocol = std::env::args().find(|arg| arg == "uci" || arg == "xboard");
+    if protocol.is_some() || !cfg!(feature = "tui") || !std::io::stdin().is_terminal() {
+        let mut lines = std::io::stdin().lines().map_while(Result::ok).peekable();
+        let is_xboard = match protocol {
+            Some(protocol) => protocol == "xboard",
+            None => lines.peek().is_some_and(|line| line.trim() == "xboard"),
+        };
+        if is_xboard {
+            cecp(engine, lines);
+        } else {
+            uci(engine, lines);
+        }
+        // The engine threads never return, so leave without waiting for them or dropping the channels under them.
+        std::process::exit(0);
+    }
+
+    #[cfg(feature = "tui")]
+    {
+        use std::time::Duration;
+
+        use chess::core::search::SearchLimits;
+
+        use crate::app::{App, DEFAULT_MOVE_TIME};
+
+        // The TUI engine thinks for `--movetime <milliseconds>` on each move, or down to `--depth <plies>`.
+        let depth = argument("--depth").and_then(|depth| depth.parse::<usize>().ok());
+        let movetime = argument("--movetime").and_then(|movetime| movetime.parse::<u64>().ok()).map(Duration::from_millis);
+        let search_limits = SearchLimits {
+            depth,
+            movetime: movetime.or(if depth.is_some() { None } else { Some(DEFAULT_MOVE_TIME) }),
+            ..SearchLimits::default()
+        };
+        let _ = App::new(engine, search_limits).run();
+        ratatui::restore();
+    }
 }
\ No newline at end of file
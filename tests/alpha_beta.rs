@@ -0,0 +1,98 @@
+use std::sync::atomic::AtomicBool;
+
+use chess::core::{chess::{board_state::{Evaluation, PositionResult}, position::Position}, engine::{alpha_beta::{AlphaBeta, alpha_beta_search}, transposition_table::TranspositionTable}, search::{SearchInfo, SearchLimits, SearchOptions}};
+
+/// The SAN of the move a search of `fen` to `depth` plays, and what it reported after each depth.
+fn search(fen: &str, depth: usize) -> (String, Vec<SearchInfo>) {
+    let position = Position::from_fen(fen).unwrap();
+    let table = TranspositionTable::new(1);
+    let stop = AtomicBool::new(false);
+    let mut reports = vec![];
+    let limits = SearchLimits { depth: Some(depth), ..Default::default() };
+    let best_move = alpha_beta_search(&position, limits, SearchOptions::default(), &table, &stop, |info| reports.push(info.clone()));
+    (position.to_san(best_move.unwrap()), reports)
+}
+
+fn evaluation(reports: &[SearchInfo]) -> Evaluation {
+    reports.last().unwrap().evaluation
+}
+
+#[test]
+fn a_mate_in_one_is_played() {
+    let (played, reports) = search("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 4);
+    assert_eq!(played, "Ra8#");
+    assert!(evaluation(&reports) == Evaluation { result: PositionResult::Win, score: 1 }, "{}", evaluation(&reports));
+}
+
+#[test]
+fn a_mate_in_two_is_played_with_its_line() {
+    let fen = "7k/8/8/8/8/8/R7/1R4K1 w - - 0 1";
+    let (played, reports) = search(fen, 6);
+    assert!(played == "Ra7" || played == "Rb7", "{}", played);
+    assert!(evaluation(&reports) == Evaluation { result: PositionResult::Win, score: 3 }, "{}", evaluation(&reports));
+    // The line ends in the mate it announces.
+    let mut position = Position::from_fen(fen).unwrap();
+    for mv in &reports.last().unwrap().principal_variation {
+        position.play(*mv);
+    }
+    assert!(position.legal_moves().is_empty() && position.board.in_check());
+}
+
+#[test]
+fn the_side_being_mated_counts_the_plies_to_it() {
+    let (played, reports) = search("7k/R7/1R6/8/8/8/8/6K1 b - - 0 1", 4);
+    assert_eq!(played, "Kg8");
+    assert!(evaluation(&reports) == Evaluation { result: PositionResult::Loss, score: 2 }, "{}", evaluation(&reports));
+}
+
+#[test]
+fn deepening_stops_once_a_mate_is_found() {
+    let (_, reports) = search("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 8);
+    assert_eq!(reports.iter().map(|info| info.depth).collect::<Vec<_>>(), vec![1]);
+}
+
+#[test]
+fn every_depth_up_to_the_limit_is_reported() {
+    let (played, reports) = search("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", 4);
+    assert_eq!(played, "Rxd5");
+    assert_eq!(reports.iter().map(|info| info.depth).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+    assert!(evaluation(&reports) == Evaluation { result: PositionResult::Scored, score: 5 }, "{}", evaluation(&reports));
+}
+
+#[test]
+fn a_defended_pawn_is_not_taken_with_the_queen() {
+    let (played, reports) = search("4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1", 4);
+    assert_ne!(played, "Qxd5");
+    assert!(evaluation(&reports) == Evaluation { result: PositionResult::Scored, score: 7 }, "{}", evaluation(&reports));
+}
+
+#[test]
+fn a_search_stopped_before_it_starts_still_has_a_legal_move() {
+    let position = Position::initial();
+    let table = TranspositionTable::new(1);
+    let stop = AtomicBool::new(true);
+    let best_move = alpha_beta_search(&position, SearchLimits::default(), SearchOptions::default(), &table, &stop, |_| {});
+    assert!(position.legal_moves().contains(&best_move.unwrap()));
+}
+
+#[test]
+fn a_position_without_moves_has_no_best_move() {
+    let position = Position::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+    let table = TranspositionTable::new(1);
+    let stop = AtomicBool::new(false);
+    let limits = SearchLimits { depth: Some(3), ..Default::default() };
+    assert!(alpha_beta_search(&position, limits, SearchOptions::default(), &table, &stop, |_| {}).is_none());
+}
+
+#[test]
+fn the_root_search_finds_the_best_move_whichever_it_starts_with() {
+    let position = Position::from_fen("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1").unwrap();
+    let table = TranspositionTable::new(1);
+    let stop = AtomicBool::new(false);
+    let mut alpha_beta = AlphaBeta::new(&position, &stop, None, SearchOptions::default(), &table);
+    let first_move = position.parse_move("Ke2").unwrap();
+    let (evaluation, principal_variation) = alpha_beta.search_root(2, Some(first_move));
+    assert_eq!(position.to_san(principal_variation[0]), "Rxd5");
+    assert!(evaluation == Evaluation { result: PositionResult::Scored, score: 5 }, "{}", evaluation);
+    assert!(alpha_beta.nodes() > 0 && !alpha_beta.is_stopped());
+}
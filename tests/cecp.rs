@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+use chess::core::{cecp::{TimeControl, cecp_score, parse_level, parse_st, result_line}, chess::{board_state::{Evaluation, PositionResult}, position::{Color, Position}}};
+
+fn seconds(seconds: u64) -> Duration {
+    Duration::from_secs(seconds)
+}
+
+fn position_at_move(fullmove_number: usize) -> Position {
+    Position::from_fen(&format!("4k3/8/8/8/8/8/8/4K2R w K - 0 {}", fullmove_number)).unwrap()
+}
+
+#[test]
+fn levels_are_read() {
+    assert_eq!(parse_level(&["40", "5", "0"]), Some((40, seconds(300), seconds(0))));
+    assert_eq!(parse_level(&["0", "2:30", "1"]), Some((0, seconds(150), seconds(1))));
+    assert_eq!(parse_level(&["0", "0.5", "0.25"]), Some((0, seconds(30), Duration::from_millis(250))));
+}
+
+#[test]
+fn malformed_levels_are_rejected() {
+    for arguments in [
+        &["40", "5"][..],
+        &["40", "5", "0", "1"],
+        &["forty", "5", "0"],
+        &["-1", "5", "0"],
+        &["40", "five", "0"],
+        &["40", "2:", "0"],
+        &["40", "2:x", "0"],
+        &["40", "-5", "0"],
+        &["40", "5", "-1"],
+        &["40", "5", "inf"],
+    ] {
+        assert_eq!(parse_level(arguments), None, "{:?}", arguments);
+    }
+}
+
+#[test]
+fn times_per_move_are_read() {
+    assert_eq!(parse_st("10"), Some(seconds(10)));
+    assert_eq!(parse_st("0.5"), Some(Duration::from_millis(500)));
+    for seconds in ["", "ten", "-1", "nan", "inf", "1e300"] {
+        assert_eq!(parse_st(seconds), None, "{:?}", seconds);
+    }
+}
+
+#[test]
+fn the_clocks_go_to_the_engine_and_its_opponent() {
+    let time_control = TimeControl {
+        base: seconds(300),
+        increment: seconds(2),
+        engine_clock: Some(seconds(60)),
+        opponent_clock: Some(seconds(90)),
+        ..Default::default()
+    };
+    let limits = time_control.search_limits(&position_at_move(1), Color::Black);
+    assert_eq!((limits.btime, limits.binc), (Some(seconds(60)), Some(seconds(2))));
+    assert_eq!((limits.wtime, limits.winc), (Some(seconds(90)), Some(seconds(2))));
+    assert_eq!(limits.movestogo, None);
+    // Until `time` and `otim` are sent, both clocks are at the base time.
+    let time_control = TimeControl { engine_clock: None, opponent_clock: None, ..time_control };
+    let limits = time_control.search_limits(&position_at_move(1), Color::White);
+    assert_eq!((limits.wtime, limits.btime), (Some(seconds(300)), Some(seconds(300))));
+}
+
+#[test]
+fn the_moves_to_go_count_down_to_the_end_of_each_session() {
+    let time_control = TimeControl { moves_per_session: 40, base: seconds(300), ..Default::default() };
+    for (fullmove_number, moves_to_go) in [(1, 40), (2, 39), (39, 2), (40, 1), (41, 40), (80, 1), (81, 40)] {
+        let limits = time_control.search_limits(&position_at_move(fullmove_number), Color::White);
+        assert_eq!(limits.movestogo, Some(moves_to_go), "move {}", fullmove_number);
+    }
+}
+
+#[test]
+fn fixed_times_and_depths_need_no_clock() {
+    let time_control = TimeControl { base: seconds(300), time_per_move: Some(seconds(5)), depth: Some(7), ..Default::default() };
+    let limits = time_control.search_limits(&position_at_move(1), Color::White);
+    assert_eq!((limits.movetime, limits.depth), (Some(seconds(5)), Some(7)));
+    assert_eq!((limits.wtime, limits.btime), (None, None));
+    let time_control = TimeControl { depth: Some(3), ..Default::default() };
+    let limits = time_control.search_limits(&position_at_move(1), Color::White);
+    assert_eq!((limits.movetime, limits.depth, limits.wtime), (None, Some(3), None));
+}
+
+#[test]
+fn scores_are_in_centipawns_and_mates_in_moves() {
+    let score = |result, score| cecp_score(&Evaluation { result, score });
+    assert_eq!(score(PositionResult::Scored, 3), 300);
+    assert_eq!(score(PositionResult::Scored, -2), -200);
+    assert_eq!(score(PositionResult::Draw, 0), 0);
+    assert_eq!(score(PositionResult::Win, 1), 100001);
+    assert_eq!(score(PositionResult::Win, 3), 100002);
+    assert_eq!(score(PositionResult::Loss, 0), -100000);
+    assert_eq!(score(PositionResult::Loss, 2), -100001);
+}
+
+#[test]
+fn results_say_how_the_game_ended() {
+    let mut position = Position::initial();
+    for notation in ["f3", "e5", "g4", "Qh4#"] {
+        let mv = position.parse_move(notation).unwrap();
+        position.play(mv);
+    }
+    assert_eq!(result_line(&position), "0-1 {Black mates}");
+    let stalemate = Position::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    assert_eq!(result_line(&stalemate), "1/2-1/2 {Stalemate}");
+    let bare_kings = Position::from_fen("7k/8/6K1/8/8/8/8/8 w - - 0 1").unwrap();
+    assert_eq!(result_line(&bare_kings), "1/2-1/2 {Insufficient material}");
+    let fifty_moves = Position::from_fen("7k/8/6K1/8/8/8/8/R7 w - - 100 80").unwrap();
+    assert_eq!(result_line(&fifty_moves), "1/2-1/2 {Draw by the fifty-move rule}");
+}
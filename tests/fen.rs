@@ -0,0 +1,75 @@
+use chess::core::chess::{fen::{FenError, INITIAL_FEN}, position::{Color, Position}};
+
+fn error(fen: &str) -> FenError {
+    Position::from_fen(fen).unwrap_err()
+}
+
+#[test]
+fn positions_are_written_back_as_they_were_read() {
+    for fen in [
+        INITIAL_FEN,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "rnbqkbnr/pppp1ppp/8/8/3Pp3/8/PPP1PPPP/RNBQKBNR b Kq d3 0 2",
+        "r3k2r/8/8/8/8/8/8/R3K2R b Qk - 17 42",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ] {
+        assert_eq!(Position::from_fen(fen).unwrap().to_fen(), fen);
+    }
+}
+
+#[test]
+fn the_position_read_is_the_one_played_to() {
+    let mut position = Position::initial();
+    for notation in ["e4", "c5", "Nf3"] {
+        let mv = position.parse_move(notation).unwrap();
+        position.play(mv);
+    }
+    let fen = "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2";
+    assert_eq!(position.to_fen(), fen);
+    let read = Position::from_fen(fen).unwrap();
+    assert_eq!(read.board, position.board);
+    assert_eq!(read.side_to_move, Color::Black);
+}
+
+#[test]
+fn the_clocks_can_be_left_out() {
+    let position = Position::from_fen("4k3/8/8/8/8/8/8/4K3 b - -").unwrap();
+    assert_eq!(position.to_fen(), "4k3/8/8/8/8/8/8/4K3 b - - 0 1");
+}
+
+#[test]
+fn an_en_passant_square_no_pawn_can_take_on_is_dropped() {
+    let position = Position::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+    assert_eq!(position.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+}
+
+#[test]
+fn every_field_is_checked() {
+    assert_eq!(error("4k3/8/8/8/8/8/8/4K3 w -"), FenError::WrongFieldCount(3));
+    assert_eq!(error("4k3/8/8/8/8/8/4K3 w - - 0 1"), FenError::WrongRankCount(7));
+    assert_eq!(error("4k3/8/8/8/8/8/8/4K2X w - - 0 1"), FenError::InvalidPiece('X'));
+    assert_eq!(error("4k3/8/8/8/8/8/8/4K2 w - - 0 1"), FenError::WrongRankLength { rank: 1, length: 7 });
+    assert_eq!(error("4k4/8/8/8/8/8/8/4K3 w - - 0 1"), FenError::WrongRankLength { rank: 8, length: 9 });
+    assert_eq!(error("4k3/8/8/8/8/8/8/4K3 x - - 0 1"), FenError::InvalidSideToMove(String::from("x")));
+    assert_eq!(error("r3k2r/8/8/8/8/8/8/R3K2R w KX - 0 1"), FenError::InvalidCastling(String::from("KX")));
+    assert_eq!(error("r3k2r/8/8/8/8/8/8/R3K2R w KK - 0 1"), FenError::InvalidCastling(String::from("KK")));
+    assert_eq!(error("r3k2r/8/8/8/8/8/8/R3K3 w K - 0 1"), FenError::UnavailableCastling('K'));
+    assert_eq!(error("4k3/8/8/3pP3/8/8/8/4K3 w - d5 0 1"), FenError::InvalidEnPassant(String::from("d5")));
+    assert_eq!(error("4k3/8/8/4P3/8/8/8/4K3 w - d6 0 1"), FenError::InvalidEnPassant(String::from("d6")));
+    assert_eq!(error("4k3/8/8/8/8/8/8/4K3 w - z9 0 1"), FenError::InvalidEnPassant(String::from("z9")));
+    assert_eq!(error("4k3/8/8/8/8/8/8/4K3 w - - x 1"), FenError::InvalidClock(String::from("x")));
+    assert_eq!(error("4k3/8/8/8/8/8/8/4K3 w - - 0 0"), FenError::InvalidClock(String::from("0")));
+    assert_eq!(error("4k3/8/8/8/8/8/8/8 w - - 0 1"), FenError::WrongKingCount { color: Color::White, count: 0 });
+    assert_eq!(error("3kk3/8/8/8/8/8/8/4K3 w - - 0 1"), FenError::WrongKingCount { color: Color::Black, count: 2 });
+    assert_eq!(error("P3k3/8/8/8/8/8/8/4K3 w - - 0 1"), FenError::PawnOnBackRank(String::from("a8")));
+    assert_eq!(error("4k3/8/8/8/8/8/8/4K2p b - - 0 1"), FenError::PawnOnBackRank(String::from("h1")));
+}
+
+#[test]
+fn the_side_not_to_move_cannot_be_in_check() {
+    assert_eq!(error("7k/8/8/8/8/8/1Q6/K7 w - - 0 1"), FenError::OpponentInCheck(Color::Black));
+    assert_eq!(error("k7/8/8/8/8/8/8/K6r b - - 0 1"), FenError::OpponentInCheck(Color::White));
+    // The side to move may be in check.
+    assert!(Position::from_fen("7k/8/8/8/8/8/1Q6/K7 b - - 0 1").is_ok());
+}
@@ -0,0 +1,57 @@
+use chess::core::chess::{board::Board, board_state::PositionResult};
+
+fn is_insufficient(fen: &str) -> bool {
+    Board::from_fen(fen).unwrap().is_insufficient_material()
+}
+
+#[test]
+fn lone_kings_and_a_single_minor_piece_cannot_mate() {
+    for fen in [
+        "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/3NK3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/2B1K3 b - - 0 1",
+        "2b1k3/8/8/8/8/8/8/4K3 w - - 0 1",
+        "4kn2/8/8/8/8/8/8/4K3 b - - 0 1",
+    ] {
+        assert!(is_insufficient(fen), "{}", fen);
+    }
+}
+
+#[test]
+fn bishops_all_on_one_color_cannot_mate() {
+    // c1 and f8 are both dark squares, as are c1, e3 and a5.
+    assert!(is_insufficient("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1"));
+    assert!(is_insufficient("4k3/8/8/B7/8/4B3/8/2B1K3 b - - 0 1"));
+}
+
+#[test]
+fn enough_material_to_mate_is_not_a_draw() {
+    for fen in [
+        // Bishops on both colors, on one side or split between the sides.
+        "4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1",
+        "4k1b1/8/8/8/8/8/8/2B1K3 w - - 0 1",
+        // Knights can mate with help, or against a piece that blocks the king in.
+        "4k3/8/8/8/8/8/8/1N2K1N1 w - - 0 1",
+        "4kn2/8/8/8/8/8/8/3NK3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/2B1KN2 w - - 0 1",
+        // Pawns, rooks and queens.
+        "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+        "4k3/7p/8/8/8/8/8/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/R3K3 w - - 0 1",
+        "3qk3/8/8/8/8/8/8/4K3 w - - 0 1",
+    ] {
+        assert!(!is_insufficient(fen), "{}", fen);
+    }
+}
+
+#[test]
+fn insufficient_material_evaluates_as_a_draw_without_moves() {
+    for fen in ["4k3/8/8/8/8/8/8/3NK3 w - - 0 1", "4k3/8/8/8/8/8/8/3NK3 b - - 0 1"] {
+        let (evaluation, moves) = Board::from_fen(fen).unwrap().get_evaluation();
+        assert!(evaluation.result == PositionResult::Draw && evaluation.score == 0, "{}: {}", fen, evaluation);
+        assert!(moves.is_empty(), "{}", fen);
+    }
+    let (evaluation, moves) = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap().get_evaluation();
+    assert!(evaluation.result == PositionResult::Scored && evaluation.score == 1, "{}", evaluation);
+    assert!(!moves.is_empty());
+}
@@ -0,0 +1,43 @@
+use chess::core::chess::{bitboard::Bitboards, board::Board, chess_move::Move, position::Position};
+
+// Positions chosen for their castling, en passant, promotions and pins, from the usual perft suites.
+const POSITIONS: [&str; 7] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "8/8/8/2k5/3Pp3/8/8/4K2R b K d3 0 1",
+];
+
+fn sorted(mut moves: Vec<Move>) -> Vec<Move> {
+    moves.sort();
+    moves
+}
+
+/// Compares both generators on every position `depth` plies below `board`.
+fn compare(board: &mut Board, depth: usize) {
+    assert_eq!(*board.bitboards(), Bitboards::from_pieces(board.pieces()), "bitboards differ from the pieces of\n{}", board);
+    let moves = sorted(board.find_moves());
+    assert_eq!(moves, sorted(board.find_moves_mailbox()), "pseudo-legal moves differ on\n{}", board);
+    let legal_moves = sorted(board.legal_moves());
+    assert_eq!(legal_moves, sorted(board.legal_moves_mailbox()), "legal moves differ on\n{}", board);
+    assert_eq!(board.in_check(), board.is_king_attacked(chess::core::chess::piece::WHITE), "check differs on\n{}", board);
+    if depth == 0 {
+        return;
+    }
+    for mv in legal_moves {
+        let undo = board.make_move(mv);
+        compare(board, depth - 1);
+        board.unmake_move(mv, undo);
+    }
+}
+
+#[test]
+fn bitboards_generate_the_same_moves_as_the_mailbox() {
+    for fen in POSITIONS {
+        let mut board = Position::from_fen(fen).unwrap().board;
+        compare(&mut board, 2);
+    }
+}
@@ -0,0 +1,118 @@
+use chess::core::{chess::{chess_move::Move, position::Position}, engine::move_ordering::{MoveOrdering, loses_exchange, mvv_lva, order_moves}};
+
+// The queen on d5 can be taken by a pawn, a knight and a queen, and the pawn on b5 by the knight, which the pawn on a6
+// takes back.
+const CAPTURES: &str = "4k3/8/p7/1p1q4/4P3/2N5/8/3QK3 w - - 0 1";
+
+fn position() -> Position {
+    Position::from_fen(CAPTURES).unwrap()
+}
+
+fn parse(position: &Position, san: &str) -> Move {
+    position.parse_move(san).unwrap()
+}
+
+fn to_san(position: &Position, moves: &[Move]) -> Vec<String> {
+    moves.iter().map(|mv| position.to_san(*mv)).collect()
+}
+
+/// The SAN of the moves of `CAPTURES` as `ordering` sorts them at `ply`.
+fn ordered(ordering: &MoveOrdering, ply: usize, previous_move: Option<Move>) -> Vec<String> {
+    let position = position();
+    let mut moves = position.legal_moves();
+    ordering.order(&position.board, &mut moves, None, ply, previous_move);
+    to_san(&position, &moves)
+}
+
+fn index_of(moves: &[String], san: &str) -> usize {
+    moves.iter().position(|mv| mv == san).unwrap()
+}
+
+#[test]
+fn the_biggest_victim_and_smallest_attacker_come_first() {
+    let position = position();
+    let scores: Vec<i32> = ["exd5", "Nxd5", "Qxd5", "Nxb5", "Kf2"].iter().map(|san| mvv_lva(&position.board, &parse(&position, san))).collect();
+    assert!(scores.is_sorted_by(|a, b| a > b), "{:?}", scores);
+}
+
+#[test]
+fn only_captures_that_are_taken_back_at_a_loss_lose_the_exchange() {
+    let position = position();
+    assert!(loses_exchange(&position.board, &parse(&position, "Nxb5")));
+    for san in ["exd5", "Nxd5", "Qxd5", "Kf2"] {
+        assert!(!loses_exchange(&position.board, &parse(&position, san)), "{}", san);
+    }
+}
+
+#[test]
+fn the_hash_move_comes_first_and_captures_by_mvv_lva_next() {
+    let position = position();
+    let mut moves = position.legal_moves();
+    order_moves(&position.board, &mut moves, Some(parse(&position, "Kf2")));
+    let moves = to_san(&position, &moves);
+    assert_eq!(moves[..5], ["Kf2", "exd5", "Nxd5", "Qxd5", "Nxb5"]);
+}
+
+#[test]
+fn a_hash_move_that_captures_still_comes_first() {
+    let position = position();
+    let mut moves = position.legal_moves();
+    order_moves(&position.board, &mut moves, Some(parse(&position, "Nxb5")));
+    assert_eq!(to_san(&position, &moves)[..4], ["Nxb5", "exd5", "Nxd5", "Qxd5"]);
+}
+
+#[test]
+fn killers_come_after_the_winning_captures_and_before_the_losing_ones() {
+    let position = position();
+    let mut ordering = MoveOrdering::new();
+    ordering.cut_off(parse(&position, "Kf2"), 3, 2, None, &[]);
+    ordering.cut_off(parse(&position, "Qd2"), 3, 2, None, &[]);
+    let moves = ordered(&ordering, 2, None);
+    // The latest killer first.
+    assert_eq!(moves[..6], ["exd5", "Nxd5", "Qxd5", "Qd2", "Kf2", "Nxb5"]);
+}
+
+#[test]
+fn killers_only_count_at_their_own_ply() {
+    let position = position();
+    let mut ordering = MoveOrdering::new();
+    ordering.cut_off(parse(&position, "Kf2"), 3, 2, None, &[]);
+    let moves = ordered(&ordering, 4, None);
+    assert!(index_of(&moves, "Nxb5") < index_of(&moves, "Kf2"), "{:?}", moves);
+}
+
+#[test]
+fn a_countermove_comes_before_the_losing_captures() {
+    let position = position();
+    let previous_move = Move::new(52, 44, 0, 0);
+    let mut ordering = MoveOrdering::new();
+    ordering.cut_off(parse(&position, "Ke2"), 1, 0, Some(previous_move), &[]);
+    let moves = ordered(&ordering, 5, Some(previous_move));
+    assert!(index_of(&moves, "Ke2") < index_of(&moves, "Nxb5"), "{:?}", moves);
+    // Another previous move has another countermove.
+    let moves = ordered(&ordering, 5, Some(Move::new(51, 43, 0, 0)));
+    assert!(index_of(&moves, "Nxb5") < index_of(&moves, "Ke2"), "{:?}", moves);
+}
+
+#[test]
+fn the_history_orders_the_quiet_moves() {
+    let position = position();
+    let mut ordering = MoveOrdering::new();
+    let tried = [parse(&position, "Kf2"), parse(&position, "Qb3")];
+    ordering.cut_off(parse(&position, "Qg4"), 4, 1, None, &tried);
+    let moves = ordered(&ordering, 6, None);
+    let quiet_moves = &moves[index_of(&moves, "Nxb5") + 1..];
+    assert_eq!(quiet_moves[0], "Qg4");
+    // The moves tried before the cut-off fall behind the ones never tried.
+    let last = quiet_moves.len() - 2;
+    assert!(index_of(quiet_moves, "Kf2") >= last && index_of(quiet_moves, "Qb3") >= last, "{:?}", quiet_moves);
+}
+
+#[test]
+fn a_best_move_of_the_graph_search_leads_the_quiet_moves() {
+    let position = position();
+    let mut ordering = MoveOrdering::new();
+    ordering.best_move_found(parse(&position, "Nb1"));
+    let moves = ordered(&ordering, 0, None);
+    assert_eq!(moves[index_of(&moves, "Nxb5") + 1], "Nb1");
+}
@@ -0,0 +1,112 @@
+use chess::core::chess::{board::Board, position::Position};
+
+fn assert_perft(fen: &str, counts: &[usize]) {
+    for (depth, count) in counts.iter().enumerate() {
+        assert_perft_at(fen, depth + 1, *count);
+    }
+}
+
+fn assert_perft_at(fen: &str, depth: usize, count: usize) {
+    let board = Position::from_fen(fen).unwrap().board;
+    assert_eq!(board.perft(depth), count, "perft({}) of {}", depth, fen);
+}
+
+#[test]
+fn start_position() {
+    assert_perft("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &[20, 400, 8902, 197281]);
+}
+
+#[test]
+fn kiwipete() {
+    assert_perft("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", &[48, 2039, 97862]);
+}
+
+#[test]
+fn rook_endgame_with_en_passant() {
+    assert_perft("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", &[14, 191, 2812, 43238]);
+}
+
+#[test]
+fn promotions_and_castling_through_check() {
+    assert_perft("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", &[6, 264, 9467]);
+    // The same position with the colors reversed.
+    assert_perft("r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1", &[6, 264, 9467]);
+}
+
+#[test]
+fn promotion_with_discovered_check() {
+    assert_perft("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", &[44, 1486, 62379]);
+}
+
+#[test]
+fn middlegame() {
+    assert_perft("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10", &[46, 2079, 89890]);
+}
+
+// The edge cases below are only known at their deepest, which is where their traps are reached.
+
+#[test]
+fn en_passant_edge_cases() {
+    // Taking en passant would expose the king along the rank or the diagonal.
+    assert_perft_at("3k4/3p4/8/K1P4r/8/8/8/8 b - - 0 1", 6, 1134888);
+    assert_perft_at("8/8/4k3/8/2p5/8/B2P2K1/8 w - - 0 1", 6, 1015133);
+    // Taking en passant gives check.
+    assert_perft_at("8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1", 6, 1440467);
+}
+
+#[test]
+fn castling_edge_cases() {
+    // Castling gives check.
+    assert_perft_at("5k2/8/8/8/8/8/8/4K2R w K - 0 1", 6, 661072);
+    assert_perft_at("3k4/8/8/8/8/8/8/R3K3 w Q - 0 1", 6, 803711);
+    // Castling rights are lost to captures and castling is prevented by attacks.
+    assert_perft_at("r3k2r/1b4bq/8/8/8/8/7B/R3K2R w KQkq - 0 1", 4, 1274206);
+    assert_perft_at("r3k2r/8/3Q4/8/8/5q2/8/R3K2R b KQkq - 0 1", 4, 1720476);
+}
+
+#[test]
+fn promotion_edge_cases() {
+    // Promoting out of check, to give check, and underpromoting to give check.
+    assert_perft_at("2K2r2/4P3/8/8/8/8/8/3k4 w - - 0 1", 6, 3821001);
+    assert_perft_at("4k3/1P6/8/8/8/8/K7/8 w - - 0 1", 6, 217342);
+    assert_perft_at("8/P1k5/K7/8/8/8/8/8 w - - 0 1", 6, 92683);
+}
+
+#[test]
+fn checkmate_and_stalemate() {
+    assert_perft_at("K1k5/8/P7/8/8/8/8/8 w - - 0 1", 6, 2217);
+    assert_perft_at("8/k1P5/8/1K6/8/8/8/8 w - - 0 1", 7, 567584);
+    assert_perft_at("8/8/2k5/5q2/5n2/8/5K2/8 b - - 0 1", 4, 23527);
+    // A discovered check.
+    assert_perft_at("8/8/1P2K3/8/2n5/1q6/8/5k2 b - - 0 1", 5, 1004658);
+}
+
+/// Plays every line of `board` up to `depth` plies with `make_move` and `unmake_move`, checking that the key and the
+/// bitboards kept up to date along the way are those of a board built from scratch, and that unmaking restores them.
+fn assert_incremental_state(board: &mut Board, depth: usize) {
+    if depth == 0 {
+        return;
+    }
+    for mv in board.legal_moves() {
+        let before = *board;
+        let undo = board.make_move(mv);
+        let recomputed = Board::from_pieces(*board.pieces());
+        assert_eq!(board.key(), recomputed.key(), "key after {:?}", mv);
+        assert_eq!(board.bitboards(), recomputed.bitboards(), "bitboards after {:?}", mv);
+        assert_eq!(board.null_move().key(), Board::from_pieces(*board.null_move().pieces()).key(), "key after passing after {:?}", mv);
+        assert_incremental_state(board, depth - 1);
+        board.unmake_move(mv, undo);
+        assert!(*board == before, "board after unmaking {:?}", mv);
+    }
+}
+
+#[test]
+fn incremental_keys_and_bitboards_match_recomputed_ones() {
+    for fen in [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ] {
+        assert_incremental_state(&mut Position::from_fen(fen).unwrap().board, 3);
+    }
+}
@@ -0,0 +1,117 @@
+use chess::core::chess::{pgn::{PgnError, PgnGame, PgnMove, game_result}, position::Position};
+
+const ANNOTATED_GAME: &str = r#"[Event "Test"]
+[Site "?"]
+
+{Opening comment} 1. e4 $1 {Best by test} e5 (1... c5 2. Nf3 (2. Nc3) d6) 2. Nf3!? Nc6 3. Bb5 a6?? 1-0
+"#;
+
+fn play(position: &mut Position, moves: &[&str]) {
+    for notation in moves {
+        let mv = position.parse_move(notation).unwrap();
+        position.play(mv);
+    }
+}
+
+fn sans(line: &[PgnMove]) -> Vec<&str> {
+    line.iter().map(|pgn_move| pgn_move.san.as_str()).collect()
+}
+
+#[test]
+fn a_saved_game_loads_back() {
+    let mut position = Position::initial();
+    play(&mut position, &["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Ba4", "Nf6", "O-O", "Be7"]);
+    let text = PgnGame::from_position(&position).to_string();
+    let game = PgnGame::parse(&text).unwrap();
+    assert_eq!(game.tag("Result"), Some("*"));
+    assert_eq!(game.tag("FEN"), None);
+    let loaded = game.replay().unwrap();
+    assert_eq!(loaded.to_fen(), position.to_fen());
+    assert_eq!(loaded.history, position.history);
+}
+
+#[test]
+fn a_game_set_up_from_a_fen_keeps_it() {
+    let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 3 12";
+    let mut position = Position::from_fen(fen).unwrap();
+    play(&mut position, &["O-O", "Bh6", "Rfe8"]);
+    let game = PgnGame::from_position(&position);
+    assert_eq!(game.tag("SetUp"), Some("1"));
+    assert_eq!(game.tag("FEN"), Some(fen));
+    let text = game.to_string();
+    assert!(text.contains("12... O-O 13. Bh6 Rfe8"), "{}", text);
+    let loaded = PgnGame::parse(&text).unwrap();
+    assert_eq!(loaded.starting_position().unwrap().to_fen(), fen);
+    assert_eq!(loaded.replay().unwrap().to_fen(), position.to_fen());
+}
+
+#[test]
+fn variations_comments_and_nags_are_read() {
+    let game = PgnGame::parse(ANNOTATED_GAME).unwrap();
+    assert_eq!(game.tag("Event"), Some("Test"));
+    assert_eq!(game.result, "1-0");
+    assert_eq!(sans(&game.moves), ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]);
+    assert_eq!(game.moves[0].comments_before, ["Opening comment"]);
+    assert_eq!(game.moves[0].nags, [1]);
+    assert_eq!(game.moves[0].comments, ["Best by test"]);
+    assert_eq!(game.moves[1].variations.len(), 1);
+    let variation = &game.moves[1].variations[0];
+    assert_eq!(sans(variation), ["c5", "Nf3", "d6"]);
+    assert_eq!(sans(&variation[1].variations[0]), ["Nc3"]);
+    // Suffix annotations are read as their NAGs.
+    assert_eq!(game.moves[2].nags, [5]);
+    assert_eq!(game.moves[5].nags, [4]);
+    assert!(game.replay().is_ok());
+}
+
+#[test]
+fn an_annotated_game_is_written_back_as_it_was_read() {
+    let game = PgnGame::parse(ANNOTATED_GAME).unwrap();
+    let text = game.to_string();
+    let movetext = text.replace('\n', " ");
+    assert!(movetext.contains("{Opening comment} 1. e4 $1 {Best by test} 1... e5 (1... c5 2. Nf3 (2. Nc3) 2... d6) 2. Nf3 $5"), "{}", text);
+    assert_eq!(PgnGame::parse(&text).unwrap(), game);
+}
+
+#[test]
+fn quoted_tag_values_may_hold_brackets_and_quotes() {
+    let game = PgnGame::parse(r#"[Event "a]b"] [Annotator "say \"hi]\" \\o/"] 1. d4 *"#).unwrap();
+    assert_eq!(game.tag("Event"), Some("a]b"));
+    assert_eq!(game.tag("Annotator"), Some(r#"say "hi]" \o/"#));
+    assert_eq!(sans(&game.moves), ["d4"]);
+    assert_eq!(PgnGame::parse(&game.to_string()).unwrap(), game);
+}
+
+#[test]
+fn every_game_of_a_file_is_read() {
+    let games = PgnGame::parse_all("[Event \"One\"]\n1. e4 1/2-1/2\n\n[Event \"Two\"]\n1. d4 d5 0-1\n\n[Event \"Three\"]\n1. c4").unwrap();
+    let results: Vec<&str> = games.iter().map(|game| game.result.as_str()).collect();
+    assert_eq!(results, ["1/2-1/2", "0-1", "*"]);
+    assert_eq!(sans(&games[2].moves), ["c4"]);
+}
+
+#[test]
+fn the_result_follows_the_position() {
+    let mut position = Position::initial();
+    assert_eq!(game_result(&position), "*");
+    play(&mut position, &["f3", "e5", "g4", "Qh4#"]);
+    assert_eq!(game_result(&position), "0-1");
+    assert_eq!(PgnGame::from_position(&position).result, "0-1");
+    let stalemate = Position::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    assert_eq!(game_result(&stalemate), "1/2-1/2");
+    let bare_kings = Position::from_fen("7k/8/6K1/8/8/8/8/8 w - - 0 1").unwrap();
+    assert_eq!(game_result(&bare_kings), "1/2-1/2");
+}
+
+#[test]
+fn malformed_games_are_rejected() {
+    assert_eq!(PgnGame::parse("1. e4 $x"), Err(PgnError::InvalidNag(String::from("$x"))));
+    assert_eq!(PgnGame::parse("1. e4 $256"), Err(PgnError::InvalidNag(String::from("$256"))));
+    assert_eq!(PgnGame::parse("[Event \"a]b 1. e4"), Err(PgnError::UnterminatedTag));
+    assert_eq!(PgnGame::parse("[Event] 1. e4"), Err(PgnError::InvalidTag(String::from("Event"))));
+    assert_eq!(PgnGame::parse("1. e4 {unfinished"), Err(PgnError::UnterminatedComment));
+    assert_eq!(PgnGame::parse("1. e4 (1. d4"), Err(PgnError::UnbalancedVariation));
+    assert_eq!(PgnGame::parse(""), Err(PgnError::NoGame));
+    assert!(matches!(PgnGame::parse("1. e4 e5 2. Ke3").unwrap().replay(), Err(PgnError::IllegalMove { move_number, .. }) if move_number == "2."));
+    assert!(matches!(PgnGame::parse("[FEN \"8/8 w - - 0 1\"] *").unwrap().replay(), Err(PgnError::InvalidFen(_))));
+}
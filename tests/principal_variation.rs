@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use chess::core::chess::{chess_move::Move, position::Position};
+
+/// The best moves of the positions reached by playing `line` from `position`, keyed by board.
+fn best_moves(position: &Position, line: &[&str]) -> HashMap<u64, Move> {
+    let mut position = position.clone();
+    let mut best_moves = HashMap::new();
+    for san in line {
+        let mv = position.parse_move(san).unwrap();
+        best_moves.insert(position.board.key(), mv);
+        position.play(mv);
+    }
+    best_moves
+}
+
+fn follow(position: &Position, best_moves: &HashMap<u64, Move>, length: usize) -> Vec<String> {
+    let line = position.follow_line(length, |board| best_moves.get(&board.key()).copied());
+    position.line_to_san(&line)
+}
+
+#[test]
+fn a_line_stops_where_there_is_no_best_move() {
+    let position = Position::initial();
+    let best_moves = best_moves(&position, &["e4", "e5", "Nf3"]);
+    assert_eq!(follow(&position, &best_moves, 10), ["e4", "e5", "Nf3"]);
+}
+
+#[test]
+fn a_line_stops_at_its_length() {
+    let position = Position::initial();
+    let best_moves = best_moves(&position, &["e4", "e5", "Nf3", "Nc6"]);
+    assert_eq!(follow(&position, &best_moves, 2), ["e4", "e5"]);
+    assert!(follow(&position, &best_moves, 0).is_empty());
+}
+
+#[test]
+fn a_line_stops_when_the_best_moves_go_round_in_a_cycle() {
+    // The knights go out and back, which leads to the starting position again.
+    let position = Position::initial();
+    let best_moves = best_moves(&position, &["Nf3", "Nf6", "Ng1", "Ng8"]);
+    assert_eq!(follow(&position, &best_moves, 100), ["Nf3", "Nf6", "Ng1", "Ng8"]);
+}
+
+#[test]
+fn a_line_stops_at_a_cycle_that_does_not_return_to_its_start() {
+    // Bf1 leads back to the position after Nf3, whose best move Nc6 would start the cycle over.
+    let position = Position::from_fen("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2").unwrap();
+    let best_moves = best_moves(&position, &["Nf3", "Nc6", "Bb5", "Nb8", "Bf1"]);
+    assert_eq!(follow(&position, &best_moves, 100), ["Nf3", "Nc6", "Bb5", "Nb8", "Bf1"]);
+}
@@ -0,0 +1,91 @@
+use chess::core::{chess::{board::Board, board_state::{Evaluation, PositionResult}}, engine::quiescence::Quiescence};
+
+fn scored(score: i32) -> Evaluation {
+    Evaluation { result: PositionResult::Scored, score }
+}
+
+/// The quiescence evaluation of `fen` and the number of moves it searched to get it.
+fn evaluate(fen: &str, checks: bool) -> (Evaluation, usize) {
+    let mut quiescence = Quiescence::new(checks);
+    let evaluation = quiescence.evaluate(&Board::from_fen(fen).unwrap());
+    (evaluation, quiescence.nodes)
+}
+
+/// `evaluate` within the window `alpha`..`beta`.
+fn evaluate_window(fen: &str, alpha: Evaluation, beta: Evaluation) -> (Evaluation, usize) {
+    let mut quiescence = Quiescence::new(false);
+    let evaluation = quiescence.search_window(&mut Board::from_fen(fen).unwrap(), alpha, beta);
+    (evaluation, quiescence.nodes)
+}
+
+#[test]
+fn a_quiet_position_stands_pat() {
+    let (evaluation, nodes) = evaluate("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1", false);
+    assert!(evaluation == scored(1), "{}", evaluation);
+    assert_eq!(nodes, 0);
+}
+
+#[test]
+fn a_hanging_piece_is_taken() {
+    let (evaluation, nodes) = evaluate("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", false);
+    assert!(evaluation == scored(5), "{}", evaluation);
+    assert!(nodes >= 1);
+}
+
+#[test]
+fn a_capture_that_loses_the_exchange_is_not_tried() {
+    // The queen would be taken back by the pawn on e6.
+    let (evaluation, nodes) = evaluate("4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1", false);
+    assert!(evaluation == scored(7), "{}", evaluation);
+    assert_eq!(nodes, 0);
+}
+
+#[test]
+fn an_exchange_is_played_out() {
+    // The pawn takes the knight and is taken back, which leaves white a pawn down instead of a knight.
+    let (evaluation, _) = evaluate("4k3/8/4p3/3n4/4P3/8/8/4K3 w - - 0 1", false);
+    assert!(evaluation == scored(-1), "{}", evaluation);
+}
+
+#[test]
+fn a_side_in_check_cannot_stand_pat() {
+    // Every way out of the check leaves the rook on h1 to be taken.
+    let (evaluation, _) = evaluate("4k3/8/8/8/8/8/8/r3K2R w - - 0 1", false);
+    assert!(evaluation == scored(-5), "{}", evaluation);
+}
+
+#[test]
+fn a_mated_side_has_no_stand_pat_to_fall_back_on() {
+    let (evaluation, _) = evaluate("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1", false);
+    assert!(evaluation == Evaluation { result: PositionResult::Loss, score: 0 }, "{}", evaluation);
+}
+
+#[test]
+fn a_capture_too_small_to_reach_alpha_is_pruned() {
+    // Standing pat is worth 4 and the pawn 1, which even with the margin of 2 cannot reach 8.
+    let fen = "4k3/8/8/3p4/8/8/8/3RK3 w - - 0 1";
+    let (evaluation, nodes) = evaluate_window(fen, scored(8), scored(10));
+    assert!(evaluation == scored(4), "{}", evaluation);
+    assert_eq!(nodes, 0);
+    // Within the margin of alpha it is searched.
+    let (evaluation, nodes) = evaluate_window(fen, scored(6), scored(10));
+    assert!(evaluation == scored(5), "{}", evaluation);
+    assert_eq!(nodes, 1);
+}
+
+#[test]
+fn standing_pat_at_beta_cuts_off() {
+    let (evaluation, nodes) = evaluate_window("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", scored(-12), scored(-10));
+    assert!(evaluation == scored(-4), "{}", evaluation);
+    assert_eq!(nodes, 0);
+}
+
+#[test]
+fn checks_are_only_tried_when_asked_for() {
+    let fen = "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1";
+    let (evaluation, nodes) = evaluate(fen, false);
+    assert!(evaluation == scored(2), "{}", evaluation);
+    assert_eq!(nodes, 0);
+    let (evaluation, _) = evaluate(fen, true);
+    assert!(evaluation == Evaluation { result: PositionResult::Win, score: 1 }, "{}", evaluation);
+}
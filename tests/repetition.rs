@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use chess::core::{chess::{board::Board, position::{DrawReason, Position}}, engine::repetition::{SearchDraw, SearchPath, search_draw}};
+
+fn play(position: &mut Position, moves: &[&str]) {
+    for notation in moves {
+        let mv = position.parse_move(notation).unwrap();
+        position.play(mv);
+    }
+}
+
+/// The search path through the boards of `moves` played from `root`, with the board it ends on.
+fn search_path(root: &Position, moves: &[&str]) -> (Option<Arc<SearchPath>>, Board) {
+    let mut position = root.clone();
+    let mut path = None;
+    for notation in moves {
+        path = SearchPath::extend(&path, &position.board);
+        let mv = position.parse_move(notation).unwrap();
+        position.play(mv);
+    }
+    (path, position.board)
+}
+
+#[test]
+fn threefold_repetition_in_the_game() {
+    let mut game = Position::initial();
+    play(&mut game, &["Nf3", "Nf6", "Ng1", "Ng8"]);
+    assert_eq!(game.repetition_count(), 2);
+    assert_eq!(game.draw_reason(), None);
+    play(&mut game, &["Nf3", "Nf6", "Ng1", "Ng8"]);
+    assert_eq!(game.repetition_count(), 3);
+    assert_eq!(game.draw_reason(), Some(DrawReason::ThreefoldRepetition));
+}
+
+#[test]
+fn searching_back_into_a_position_of_the_game_repeats_it() {
+    let mut game = Position::initial();
+    play(&mut game, &["Nf3", "Nf6", "Ng1"]);
+    let root_ply = game.history.len();
+    let (_, board) = search_path(&game, &["Ng8"]);
+    assert!(game.repeats_history(&board, root_ply + 1));
+    let (_, board) = search_path(&game, &["Nc6"]);
+    assert!(!game.repeats_history(&board, root_ply + 1));
+}
+
+#[test]
+fn going_back_and_forth_on_the_search_path_repeats() {
+    let game = Position::initial();
+    let (path, board) = search_path(&game, &["Nf3", "Nf6", "Ng1", "Ng8"]);
+    assert!(SearchPath::repeats(&path, &board));
+    // Coming back to the root repeats the game itself.
+    assert_eq!(search_draw(&game, 0, &board, &path, 4), SearchDraw::RepeatsGame);
+    let (path, board) = search_path(&game, &["e4", "e5", "Nf3", "Nf6", "Ng1", "Ng8"]);
+    assert!(SearchPath::repeats(&path, &board));
+    assert_eq!(search_draw(&game, 0, &board, &path, 6), SearchDraw::OnPath);
+}
+
+#[test]
+fn a_transposition_is_not_a_repetition() {
+    let game = Position::initial();
+    // Both lines reach the same position, but neither goes through it twice.
+    let (path, board) = search_path(&game, &["Nf3", "Nf6", "Nc3", "Nc6"]);
+    let (other_path, other_board) = search_path(&game, &["Nc3", "Nc6", "Nf3", "Nf6"]);
+    assert_eq!(board, other_board);
+    assert!(!SearchPath::repeats(&path, &board));
+    assert!(!SearchPath::repeats(&other_path, &other_board));
+    assert_eq!(search_draw(&game, 0, &board, &path, 4), SearchDraw::NotDrawn);
+    // Taking the queen side knights back reaches a position that only the first line went through.
+    let (path, board) = search_path(&game, &["Nf3", "Nf6", "Nc3", "Nc6", "Nb1", "Nb8"]);
+    assert!(SearchPath::repeats(&path, &board));
+    let (other_path, other_board) = search_path(&game, &["Nc3", "Nc6", "Nf3", "Nf6", "Nb1", "Nb8"]);
+    assert_eq!(board, other_board);
+    assert!(!SearchPath::repeats(&other_path, &other_board));
+}
+
+#[test]
+fn a_transposition_draws_only_on_the_path_that_repeats() {
+    let game = Position::initial();
+    let (path, board) = search_path(&game, &["Nf3", "Nf6", "Nc3", "Nc6", "Nb1", "Nb8"]);
+    let (other_path, other_board) = search_path(&game, &["Nc3", "Nc6", "Nf3", "Nf6", "Nb1", "Nb8"]);
+    assert_eq!(board, other_board);
+    assert_eq!(search_draw(&game, 0, &board, &path, 6), SearchDraw::OnPath);
+    assert_eq!(search_draw(&game, 0, &other_board, &other_path, 6), SearchDraw::NotDrawn);
+}
+
+#[test]
+fn repeating_the_game_draws_on_every_path() {
+    let mut game = Position::initial();
+    play(&mut game, &["Nf3", "Nf6"]);
+    // Both lines come back to the position of the game, one of them through a board it goes through twice.
+    let (path, board) = search_path(&game, &["Ng1", "Ng8", "Nf3", "Nf6"]);
+    let (other_path, other_board) = search_path(&game, &["Nc3", "Nc6", "Nb1", "Nb8"]);
+    assert_eq!(board, other_board);
+    assert_eq!(search_draw(&game, 2, &board, &path, 4), SearchDraw::RepeatsGame);
+    assert_eq!(search_draw(&game, 2, &other_board, &other_path, 4), SearchDraw::RepeatsGame);
+}
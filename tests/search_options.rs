@@ -0,0 +1,88 @@
+use std::sync::atomic::AtomicBool;
+
+use chess::core::{chess::{board_state::{Evaluation, PositionResult}, position::Position}, engine::{alpha_beta::alpha_beta_search, transposition_table::TranspositionTable}, search::{SearchLimits, SearchOptions}};
+
+const NONE: SearchOptions = SearchOptions {
+    quiescence_checks: false,
+    null_move_pruning: false,
+    late_move_reductions: false,
+    lmr_base: 75,
+    lmr_divisor: 225,
+    futility_pruning: false,
+    razoring: false,
+};
+
+/// Every option on its own, all of them and all of them but one, named for the failure messages.
+fn variants() -> Vec<(&'static str, SearchOptions)> {
+    let all = SearchOptions { quiescence_checks: true, ..SearchOptions::default() };
+    vec![
+        ("none", NONE),
+        ("quiescence checks", SearchOptions { quiescence_checks: true, ..NONE }),
+        ("null-move pruning", SearchOptions { null_move_pruning: true, ..NONE }),
+        ("late move reductions", SearchOptions { late_move_reductions: true, ..NONE }),
+        ("futility pruning", SearchOptions { futility_pruning: true, ..NONE }),
+        ("razoring", SearchOptions { razoring: true, ..NONE }),
+        ("all", all),
+        ("all but quiescence checks", SearchOptions { quiescence_checks: false, ..all }),
+        ("all but null-move pruning", SearchOptions { null_move_pruning: false, ..all }),
+        ("all but late move reductions", SearchOptions { late_move_reductions: false, ..all }),
+        ("all but futility pruning", SearchOptions { futility_pruning: false, ..all }),
+        ("all but razoring", SearchOptions { razoring: false, ..all }),
+    ]
+}
+
+/// Searches `fen` with every variant of the options at each of `depths`, and checks that `is_best` holds for the SAN
+/// of the move played and that the evaluation is `expected`.
+fn assert_found(fen: &str, depths: std::ops::RangeInclusive<usize>, is_best: impl Fn(&str) -> bool, expected: Evaluation) {
+    let position = Position::from_fen(fen).unwrap();
+    for depth in depths {
+        for (name, options) in variants() {
+            let table = TranspositionTable::new(1);
+            let stop = AtomicBool::new(false);
+            let mut evaluation = None;
+            let limits = SearchLimits { depth: Some(depth), ..Default::default() };
+            let best_move = alpha_beta_search(&position, limits, options, &table, &stop, |info| evaluation = Some(info.evaluation));
+            let played = position.to_san(best_move.unwrap());
+            let evaluation = evaluation.unwrap();
+            assert!(is_best(&played), "{} at depth {} with {}", played, depth, name);
+            assert!(evaluation == expected, "{} at depth {} with {}", evaluation, depth, name);
+        }
+    }
+}
+
+fn scored(score: i32) -> Evaluation {
+    Evaluation { result: PositionResult::Scored, score }
+}
+
+#[test]
+fn every_option_mates_in_one() {
+    assert_found("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 2..=5, |san| san == "Ra8#", Evaluation { result: PositionResult::Win, score: 1 });
+}
+
+#[test]
+fn every_option_mates_in_two() {
+    // The rooks take turns to cut the king off on the seventh rank and to mate on the eighth.
+    assert_found("7k/8/8/8/8/8/R7/1R4K1 w - - 0 1", 3..=6, |san| san == "Ra7" || san == "Rb7", Evaluation { result: PositionResult::Win, score: 3 });
+}
+
+#[test]
+fn every_option_sees_the_mate_coming() {
+    assert_found("7k/R7/1R6/8/8/8/8/6K1 b - - 0 1", 2..=5, |san| san == "Kg8", Evaluation { result: PositionResult::Loss, score: 2 });
+}
+
+#[test]
+fn every_option_takes_a_hanging_queen() {
+    assert_found("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", 1..=5, |san| san == "Rxd5", scored(5));
+}
+
+#[test]
+fn every_option_leaves_a_defended_pawn_to_the_queen() {
+    assert_found("4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1", 1..=5, |san| san != "Qxd5", scored(7));
+}
+
+#[test]
+fn every_option_wins_the_queen_with_a_fork() {
+    // The knight checks the king and attacks the queen at once, so the queen falls however the king escapes. The
+    // pawns keep the knight from being left alone against the king, which would be a draw.
+    assert_found("q3k3/7p/8/1N6/8/8/7P/4K3 w - - 0 1", 1..=5, |san| san == "Nc7+", scored(3));
+}
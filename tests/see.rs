@@ -0,0 +1,73 @@
+use chess::core::chess::{board::Board, position::Position};
+
+/// The exchange evaluation of `notation` played by the side to move in `fen`.
+fn see(fen: &str, notation: &str) -> i32 {
+    let position = Position::from_fen(fen).unwrap();
+    let mv = position.parse_move(notation).unwrap();
+    position.board.see(&mv)
+}
+
+fn hanging_pieces(fen: &str) -> Vec<usize> {
+    Board::from_fen(fen).unwrap().hanging_pieces()
+}
+
+#[test]
+fn taking_an_undefended_piece_wins_it() {
+    assert_eq!(see("4k3/8/8/3n4/4P3/8/8/4K3 w - - 0 1", "exd5"), 3);
+    assert_eq!(see("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", "Rxd5"), 9);
+}
+
+#[test]
+fn taking_a_defended_piece_counts_the_recapture() {
+    // The pawn on e5 is defended by the one on d6.
+    assert_eq!(see("4k3/8/3p4/4p3/8/8/8/4RK2 w - - 0 1", "Rxe5"), -4);
+    assert_eq!(see("4k3/8/3p4/4n3/8/8/8/4RK2 w - - 0 1", "Rxe5"), -2);
+    assert_eq!(see("4k3/8/5p2/4n3/3P4/8/8/4K3 w - - 0 1", "dxe5"), 2);
+    assert_eq!(see("4k3/8/3p4/4p3/8/5N2/8/4QK2 w - - 0 1", "Nxe5"), -1);
+    // The queen does not take back, as the bishop would take it.
+    assert_eq!(see("4k3/4q3/8/4p3/8/2B5/8/4RK2 w - - 0 1", "Rxe5"), 1);
+}
+
+#[test]
+fn moving_to_an_attacked_square_loses_the_piece() {
+    assert_eq!(see("4k3/8/3p4/8/8/8/8/2B1K3 w - - 0 1", "Bf4"), 0);
+    assert_eq!(see("4k3/8/3p4/8/8/8/8/2B1K3 w - - 0 1", "Be3"), 0);
+    assert_eq!(see("4k3/8/3p4/8/8/8/5B2/4K3 w - - 0 1", "Bc5"), -3);
+}
+
+#[test]
+fn sliders_behind_the_pieces_that_take_join_the_exchange() {
+    // The rook on e1 backs up the one on e2, and the queen on a1 the bishop on b2.
+    assert_eq!(see("4r1k1/8/8/4p3/8/8/4R3/4R1K1 w - - 0 1", "Rxe5"), 1);
+    assert_eq!(see("4r1k1/8/8/4p3/8/8/4R3/6K1 w - - 0 1", "Rxe5"), -4);
+    assert_eq!(see("6k1/8/8/4p3/3n4/8/1B6/Q5K1 w - - 0 1", "Bxd4"), 1);
+    assert_eq!(see("6k1/8/8/4p3/3n4/8/1B6/6K1 w - - 0 1", "Bxd4"), 0);
+}
+
+#[test]
+fn a_pawn_taking_on_the_last_rank_promotes() {
+    assert_eq!(see("3r3k/2P5/8/8/8/8/8/4K3 w - - 0 1", "cxd8=Q+"), 13);
+    assert_eq!(see("3r3k/2P5/8/8/8/8/8/4K3 w - - 0 1", "c8=Q"), -1);
+    // The king takes the new queen back.
+    assert_eq!(see("3rk3/2P5/8/8/8/8/8/4K3 w - - 0 1", "cxd8=Q+"), 4);
+}
+
+#[test]
+fn an_undefended_piece_hangs_and_a_defended_one_does_not() {
+    // The knight on a4 is attacked by the rook on a8.
+    assert_eq!(hanging_pieces("r6k/8/8/8/N7/8/8/7K w - - 0 1"), [24]);
+    assert_eq!(hanging_pieces("r6k/8/8/8/N7/1P6/8/7K w - - 0 1"), Vec::<usize>::new());
+    // A defended piece still hangs to a less valuable attacker.
+    assert_eq!(hanging_pieces("7k/8/8/1p6/N7/1P6/8/7K w - - 0 1"), [24]);
+}
+
+#[test]
+fn the_hanging_pieces_do_not_depend_on_the_side_to_move_taking_en_passant() {
+    let with_en_passant = "4k3/8/8/3pP3/8/8/8/R3K3 w - d6 0 2";
+    let without_en_passant = "4k3/8/8/3pP3/8/8/8/R3K3 w - - 0 2";
+    assert!(Board::from_fen(with_en_passant).unwrap() != Board::from_fen(without_en_passant).unwrap());
+    assert_eq!(hanging_pieces(with_en_passant), hanging_pieces(without_en_passant));
+    // The pawn that can be taken en passant does hang.
+    assert_eq!(Position::from_fen(with_en_passant).unwrap().hanging_pieces(), [35]);
+    assert_eq!(Position::from_fen(without_en_passant).unwrap().hanging_pieces(), Vec::<usize>::new());
+}
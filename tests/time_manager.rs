@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use chess::core::{chess::{chess_move::Move, position::Color}, search::SearchLimits, time_manager::TimeManager};
+
+fn milliseconds(milliseconds: u64) -> Option<Duration> {
+    Some(Duration::from_millis(milliseconds))
+}
+
+fn limits(wtime: u64, winc: u64, movestogo: Option<usize>) -> SearchLimits {
+    SearchLimits { wtime: milliseconds(wtime), winc: milliseconds(winc), movestogo, ..Default::default() }
+}
+
+#[test]
+fn the_clock_is_shared_between_the_default_number_of_moves() {
+    let time_manager = TimeManager::new(&limits(60_000, 0, None), Color::White);
+    assert_eq!(time_manager.soft_limit(), milliseconds(2_000));
+    assert_eq!(time_manager.hard_limit(), milliseconds(8_000));
+}
+
+#[test]
+fn the_clock_is_shared_between_the_moves_to_go() {
+    let time_manager = TimeManager::new(&limits(60_000, 0, Some(10)), Color::White);
+    assert_eq!(time_manager.soft_limit(), milliseconds(6_000));
+    assert_eq!(time_manager.hard_limit(), milliseconds(24_000));
+    // Four times the soft limit would be most of the clock, so no more than half of it is spent.
+    let time_manager = TimeManager::new(&limits(60_000, 0, Some(5)), Color::White);
+    assert_eq!(time_manager.soft_limit(), milliseconds(12_000));
+    assert_eq!(time_manager.hard_limit(), milliseconds(29_975));
+}
+
+#[test]
+fn most_of_the_increment_is_spent() {
+    let time_manager = TimeManager::new(&limits(60_000, 1_000, None), Color::White);
+    assert_eq!(time_manager.soft_limit(), milliseconds(2_750));
+    assert_eq!(time_manager.hard_limit(), milliseconds(11_000));
+}
+
+#[test]
+fn the_last_move_before_the_time_control_keeps_half_the_clock() {
+    let time_manager = TimeManager::new(&limits(1_000, 0, Some(1)), Color::White);
+    assert_eq!(time_manager.soft_limit(), milliseconds(475));
+    assert_eq!(time_manager.hard_limit(), milliseconds(475));
+}
+
+#[test]
+fn the_clock_of_the_side_to_move_is_used() {
+    let limits = SearchLimits { wtime: milliseconds(60_000), btime: milliseconds(30_000), ..Default::default() };
+    assert_eq!(TimeManager::new(&limits, Color::White).soft_limit(), milliseconds(2_000));
+    assert_eq!(TimeManager::new(&limits, Color::Black).soft_limit(), milliseconds(1_000));
+}
+
+#[test]
+fn a_fixed_move_time_is_used_up_to_the_hard_limit() {
+    let limits = SearchLimits { movetime: milliseconds(1_000), ..Default::default() };
+    let mut time_manager = TimeManager::new(&limits, Color::White);
+    assert_eq!(time_manager.hard_limit(), milliseconds(950));
+    assert_eq!(time_manager.next_depth_limit(), None);
+    assert!(time_manager.should_deepen(None));
+    assert!(!time_manager.is_hard_limit_reached());
+}
+
+#[test]
+fn the_hard_limit_stops_the_search() {
+    let limits = SearchLimits { movetime: milliseconds(10), ..Default::default() };
+    let mut time_manager = TimeManager::new(&limits, Color::White);
+    assert_eq!(time_manager.hard_limit(), milliseconds(0));
+    assert!(time_manager.is_hard_limit_reached());
+    assert!(!time_manager.should_deepen(None));
+    assert!(time_manager.deadline().is_some());
+}
+
+#[test]
+fn searches_without_a_clock_have_no_limit() {
+    let infinite = SearchLimits { infinite: true, wtime: milliseconds(1_000), ..Default::default() };
+    for limits in [infinite, SearchLimits { depth: Some(5), ..Default::default() }] {
+        let mut time_manager = TimeManager::new(&limits, Color::White);
+        assert_eq!(time_manager.soft_limit(), None);
+        assert_eq!(time_manager.deadline(), None);
+        assert!(time_manager.should_deepen(None));
+    }
+}
+
+#[test]
+fn a_stable_best_move_leaves_less_time_for_the_next_depth() {
+    let mut time_manager = TimeManager::new(&limits(60_000, 0, None), Color::White);
+    let best_move = Some(Move::new(12, 28, 0, 2));
+    let mut next_depth_limits = vec![];
+    for _ in 0..5 {
+        assert!(time_manager.should_deepen(best_move));
+        next_depth_limits.push(time_manager.next_depth_limit().unwrap().as_millis());
+    }
+    assert_eq!(next_depth_limits, [1_200, 1_000, 800, 600, 600]);
+    // A new best move earns the time back.
+    assert!(time_manager.should_deepen(Some(Move::new(11, 27, 0, 2))));
+    assert_eq!(time_manager.next_depth_limit(), milliseconds(1_200));
+}
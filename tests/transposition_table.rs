@@ -0,0 +1,97 @@
+use chess::core::{chess::{board_state::{Evaluation, PositionResult}, chess_move::Move, piece::QUEEN}, engine::transposition_table::{Bound, TableEntry, TranspositionTable}};
+
+fn scored(score: i32) -> Evaluation {
+    Evaluation { result: PositionResult::Scored, score }
+}
+
+fn entry(depth: usize, evaluation: Evaluation) -> TableEntry {
+    TableEntry { depth, bound: Bound::Exact, evaluation, best_move: None }
+}
+
+fn probed_depth(table: &TranspositionTable, key: u64) -> Option<usize> {
+    table.probe(key).map(|entry| entry.depth)
+}
+
+// The keys below share the first cluster of a 1 MB table, which has four entries.
+const KEYS: [u64; 6] = [1, 2, 3, 4, 5, 6];
+
+#[test]
+fn stored_entries_are_probed_back() {
+    let table = TranspositionTable::new(1);
+    let stored = [
+        TableEntry { depth: 7, bound: Bound::Lower, evaluation: scored(-3), best_move: Some(Move::new(12, 28, 0, 2)) },
+        TableEntry { depth: 0, bound: Bound::Upper, evaluation: Evaluation { result: PositionResult::Win, score: 5 }, best_move: None },
+        TableEntry { depth: 255, bound: Bound::Exact, evaluation: Evaluation { result: PositionResult::Loss, score: 0 }, best_move: Some(Move::new(52, 60, QUEEN, 1)) },
+        TableEntry { depth: 3, bound: Bound::Exact, evaluation: Evaluation { result: PositionResult::Draw, score: 0 }, best_move: Some(Move::new(4, 6, 0, 8)) },
+    ];
+    let keys = [0x0123_4567_89AB_CDEF, 0xFEDC_BA98_7654_3210, 42, u64::MAX];
+    for (key, entry) in keys.iter().zip(stored.iter()) {
+        table.store(*key, *entry);
+    }
+    for (key, entry) in keys.iter().zip(stored.iter()) {
+        assert!(table.probe(*key) == Some(*entry), "entry of key {:x} differs", key);
+    }
+    assert!(table.probe(7).is_none());
+}
+
+#[test]
+fn storing_a_position_again_keeps_its_best_move() {
+    let table = TranspositionTable::new(1);
+    let best_move = Some(Move::new(12, 28, 0, 2));
+    table.store(KEYS[0], TableEntry { best_move, ..entry(2, scored(1)) });
+    table.store(KEYS[0], entry(4, scored(2)));
+    let probed = table.probe(KEYS[0]).unwrap();
+    assert_eq!(probed.depth, 4);
+    assert!(probed.evaluation == scored(2));
+    assert!(probed.best_move == best_move);
+}
+
+#[test]
+fn the_shallowest_entry_is_replaced() {
+    let table = TranspositionTable::new(1);
+    for (key, depth) in KEYS[..4].iter().zip([5, 1, 3, 4]) {
+        table.store(*key, entry(depth, scored(0)));
+    }
+    table.store(KEYS[4], entry(2, scored(0)));
+    assert_eq!(probed_depth(&table, KEYS[1]), None);
+    assert_eq!(probed_depth(&table, KEYS[4]), Some(2));
+    for (key, depth) in [(KEYS[0], 5), (KEYS[2], 3), (KEYS[3], 4)] {
+        assert_eq!(probed_depth(&table, key), Some(depth));
+    }
+}
+
+#[test]
+fn entries_of_earlier_searches_are_replaced_first() {
+    let table = TranspositionTable::new(1);
+    for (key, depth) in KEYS[..4].iter().zip([5, 1, 3, 4]) {
+        table.store(*key, entry(depth, scored(0)));
+    }
+    table.new_search();
+    table.store(KEYS[4], entry(1, scored(0)));
+    // The shallow entry of this search outlives the deeper ones of the last search.
+    table.store(KEYS[5], entry(1, scored(0)));
+    assert_eq!(probed_depth(&table, KEYS[4]), Some(1));
+    assert_eq!(probed_depth(&table, KEYS[5]), Some(1));
+    assert_eq!(probed_depth(&table, KEYS[1]), None);
+    assert_eq!(probed_depth(&table, KEYS[2]), None);
+}
+
+#[test]
+fn scores_are_clamped_to_16_bits() {
+    let table = TranspositionTable::new(1);
+    table.store(KEYS[0], entry(1, scored(100_000)));
+    table.store(KEYS[1], entry(1, scored(-100_000)));
+    table.store(KEYS[2], entry(1, scored(i16::MIN as i32)));
+    assert!(table.probe(KEYS[0]).unwrap().evaluation == scored(i16::MAX as i32));
+    assert!(table.probe(KEYS[1]).unwrap().evaluation == scored(i16::MIN as i32));
+    assert!(table.probe(KEYS[2]).unwrap().evaluation == scored(i16::MIN as i32));
+}
+
+#[test]
+fn clearing_empties_the_table() {
+    let table = TranspositionTable::new(1);
+    table.store(KEYS[0], entry(1, scored(0)));
+    table.clear();
+    assert!(table.probe(KEYS[0]).is_none());
+    assert_eq!(table.size_mb(), 1);
+}
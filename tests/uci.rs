@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use chess::core::{chess::{board_state::{Evaluation, PositionResult}, fen::INITIAL_FEN, position::Position}, search::SearchLimits, uci::{parse_go, parse_position, score_string}};
+
+fn milliseconds(milliseconds: u64) -> Option<Duration> {
+    Some(Duration::from_millis(milliseconds))
+}
+
+fn go(command: &str) -> SearchLimits {
+    parse_go(&command.split_whitespace().collect::<Vec<&str>>())
+}
+
+fn position(command: &str) -> Result<Position, String> {
+    parse_position(&command.split_whitespace().collect::<Vec<&str>>())
+}
+
+#[test]
+fn go_reads_every_limit() {
+    assert_eq!(go(""), SearchLimits::default());
+    assert_eq!(go("depth 7"), SearchLimits { depth: Some(7), ..Default::default() });
+    assert_eq!(go("movetime 2500"), SearchLimits { movetime: milliseconds(2500), ..Default::default() });
+    assert_eq!(go("infinite"), SearchLimits { infinite: true, ..Default::default() });
+    assert_eq!(go("wtime 60000 btime 55000 winc 1000 binc 500 movestogo 12"), SearchLimits {
+        wtime: milliseconds(60000),
+        btime: milliseconds(55000),
+        winc: milliseconds(1000),
+        binc: milliseconds(500),
+        movestogo: Some(12),
+        ..Default::default()
+    });
+}
+
+#[test]
+fn go_skips_what_it_does_not_know() {
+    assert_eq!(go("ponder wtime 1000 nodes 5000 btime 2000"), SearchLimits { wtime: milliseconds(1000), btime: milliseconds(2000), ..Default::default() });
+    assert_eq!(go("infinite depth 3"), SearchLimits { infinite: true, depth: Some(3), ..Default::default() });
+    // A limit without a number is left unset.
+    assert_eq!(go("depth x movetime"), SearchLimits::default());
+}
+
+#[test]
+fn position_starts_from_the_start_position_or_a_fen() {
+    assert_eq!(position("startpos").unwrap(), Position::initial());
+    assert_eq!(position(&format!("fen {}", INITIAL_FEN)).unwrap(), Position::initial());
+    let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    assert_eq!(position(&format!("fen {}", fen)).unwrap().to_fen(), fen);
+}
+
+#[test]
+fn position_plays_the_moves() {
+    let played = position("startpos moves e2e4 c7c5 g1f3").unwrap();
+    assert_eq!(played.to_fen(), "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+    assert_eq!(played.history.len(), 3);
+    let played = position("fen r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1 moves e1g1 e8c8").unwrap();
+    assert_eq!(played.to_fen(), "2kr3r/8/8/8/8/8/8/R4RK1 w - - 2 2");
+    assert_eq!(position("startpos moves").unwrap(), Position::initial());
+}
+
+#[test]
+fn position_rejects_what_it_cannot_play() {
+    assert!(position("").is_err());
+    assert!(position("e2e4").is_err());
+    assert!(position("fen 8/8 w - - 0 1").is_err());
+    assert!(position("startpos moves e2e5").is_err());
+    assert!(position("startpos moves e2e4 e2e4").is_err());
+}
+
+#[test]
+fn scores_are_in_centipawns_and_mates_in_moves() {
+    let score = |result, score| score_string(&Evaluation { result, score });
+    assert_eq!(score(PositionResult::Scored, 3), "cp 300");
+    assert_eq!(score(PositionResult::Scored, -1), "cp -100");
+    assert_eq!(score(PositionResult::Draw, 0), "cp 0");
+    assert_eq!(score(PositionResult::Win, 1), "mate 1");
+    assert_eq!(score(PositionResult::Win, 5), "mate 3");
+    assert_eq!(score(PositionResult::Loss, 2), "mate -1");
+    assert_eq!(score(PositionResult::Loss, 4), "mate -2");
+    assert_eq!(score(PositionResult::Loss, 0), "mate 0");
+}